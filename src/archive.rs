@@ -0,0 +1,196 @@
+//! Archiving of files into folders named after their date tag (e.g.
+//! '2024/04' with monthly slash tags), in the spirit of rename
+//!
+//! The date of each file comes from its name, its modification time or
+//! a reference date. Files are only reported unless '--apply' is given,
+//! and a file already in its folder is skipped, numbered or replaced;
+//! only files archived before the run are ever replaced, so that two
+//! files of the same name archived together never overwrite each other.
+
+use std::{
+    collections::HashSet,
+    fmt::Write,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+use crate::{
+    finder::Finder,
+    rename::{self, Source},
+};
+
+/// arguments of the archive subcommand
+#[derive(Debug, clap::Args)]
+pub struct ArchiveArgs {
+    /// Files to archive
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+
+    /// Root of the dated folders (e.g. '<ROOT>/2024/04' with '-tm -s slash')
+    #[arg(long, value_name = "ROOT")]
+    root: PathBuf,
+
+    /// Copy files, instead of moving them
+    #[arg(long)]
+    copy: bool,
+
+    /// Origin of the date of each file
+    #[arg(value_enum, long, default_value_t = Source::Mtime)]
+    source: Source,
+
+    /// Use the given reference date for all the files, instead of --source
+    #[arg(long, value_name = "DATE", conflicts_with = "source")]
+    date: Option<String>,
+
+    /// Policy applied when a file already exists in its folder
+    #[arg(value_enum, long, default_value_t = Conflict::Skip)]
+    on_conflict: Conflict,
+
+    /// Move or copy files, instead of reporting what would be done
+    #[arg(long)]
+    apply: bool,
+}
+
+/// policies applied when a file already exists in its folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Conflict {
+    /// leave the file where it is
+    Skip,
+    /// append a number to the archived name (e.g. 'report_2.pdf')
+    Rename,
+    /// replace the existing file, unless archived by the same run
+    Overwrite,
+}
+
+/// move a file, copying it across file systems
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            fs::copy(from, to)?;
+            fs::remove_file(from)
+        }
+        result => result,
+    }
+}
+
+/// true if both paths lead to the same existing file
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// move or copy files into folders named after their date
+pub fn run(
+    args: &ArchiveArgs,
+    finder: &Finder,
+    parse: impl Fn(&str) -> Result<NaiveDate>,
+    folder: impl Fn(&NaiveDate) -> String,
+) -> Result<String> {
+    let date = args.date.as_deref().map(parse).transpose()?;
+    let verb = if args.copy { "copy" } else { "move" };
+
+    let mut report = String::new();
+    let mut taken: HashSet<PathBuf> = HashSet::new();
+    let (mut archived, mut skipped) = (0, 0);
+    for path in &args.files {
+        let date = match date {
+            Some(date) => Some(date),
+            None => rename::file_date(path, args.source, finder)?,
+        };
+        let (Some(date), Some(name), true) = (date, path.file_name(), path.is_file()) else {
+            writeln!(report, "skip    {} (no date or not a file)", path.display())?;
+            skipped += 1;
+            continue;
+        };
+
+        // absolute or parent folders would escape the archive root
+        let folder = folder(&date);
+        if !Path::new(&folder)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            bail!("folder '{}' is outside of the archive root", folder);
+        }
+
+        let dir = args.root.join(folder);
+        let mut target = dir.join(name);
+        if same_file(path, &target) {
+            writeln!(report, "skip    {} (already archived)", path.display())?;
+            skipped += 1;
+            continue;
+        }
+        if target.exists() || taken.contains(&target) {
+            match args.on_conflict {
+                Conflict::Skip => {
+                    writeln!(report, "skip    {} (already archived)", path.display())?;
+                    skipped += 1;
+                    continue;
+                }
+                Conflict::Rename => target = rename::numbered(&target, &taken),
+                Conflict::Overwrite if taken.contains(&target) => {
+                    target = rename::numbered(&target, &taken)
+                }
+                Conflict::Overwrite => {}
+            }
+        }
+
+        if args.apply {
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("cannot create directory '{}'", dir.display()))?;
+            if args.copy {
+                fs::copy(path, &target).map(|_| ())
+            } else {
+                move_file(path, &target)
+            }
+            .with_context(|| format!("cannot {} '{}'", verb, path.display()))?;
+        }
+
+        writeln!(
+            report,
+            "{:8}{} -> {}",
+            verb,
+            path.display(),
+            target.display()
+        )?;
+        taken.insert(target);
+        archived += 1;
+    }
+
+    let done = if args.copy { "copied" } else { "moved" };
+    if args.apply {
+        writeln!(report, "{} files {}, {} skipped", archived, done, skipped)?;
+    } else {
+        writeln!(
+            report,
+            "dry run: {} files would be {}, {} skipped, use '--apply' to proceed",
+            archived, done, skipped
+        )?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_file() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        fs::create_dir_all(dir.join("2024/04")).unwrap();
+        fs::write(dir.join("report.pdf"), "content").unwrap();
+
+        move_file(&dir.join("report.pdf"), &dir.join("2024/04/report.pdf")).unwrap();
+        assert!(!dir.join("report.pdf").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("2024/04/report.pdf")).unwrap(),
+            "content"
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use std::io::BufRead;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+use crate::utils::{self, ParseOptions};
+
+/// policy applied to reference dates that cannot be converted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnError {
+    /// drop the invalid entry
+    Skip,
+    /// stop processing and report an error
+    Fail,
+    /// copy the invalid entry unchanged to the output
+    Passthrough,
+}
+
+/// input layout of the batch stream
+#[derive(Debug, Clone, Copy)]
+pub enum Layout {
+    /// one reference date per line
+    Lines,
+    /// reference dates stored in a CSV column (0-based index)
+    Csv { column: usize, delimiter: u8 },
+}
+
+/// single entry read from the batch stream
+#[derive(Debug, PartialEq, Eq)]
+pub enum Entry {
+    /// a valid reference date
+    Date(NaiveDate),
+    /// an invalid reference date, to be copied unchanged to the output
+    Passthrough(String),
+}
+
+/// iterator over the reference dates read from a batch stream
+pub struct Entries<R: BufRead, F: Fn(&NaiveDate) -> Option<NaiveDate>> {
+    source: Source<R>,
+    options: ParseOptions,
+    on_error: OnError,
+    offset: F,
+}
+
+enum Source<R: BufRead> {
+    Lines {
+        input: R,
+        line: String,
+        number: u64,
+    },
+    Csv {
+        reader: csv::Reader<R>,
+        record: csv::StringRecord,
+        column: usize,
+    },
+}
+
+/// read reference dates from `input`, shifted by `offset`, applying the
+/// given error policy to dates that cannot be read or shifted
+pub fn entries<R: BufRead, F: Fn(&NaiveDate) -> Option<NaiveDate>>(
+    input: R,
+    layout: Layout,
+    options: ParseOptions,
+    on_error: OnError,
+    offset: F,
+) -> Entries<R, F> {
+    let source = match layout {
+        Layout::Lines => Source::Lines {
+            input,
+            line: String::new(),
+            number: 0,
+        },
+        Layout::Csv { column, delimiter } => Source::Csv {
+            reader: csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .delimiter(delimiter)
+                .from_reader(input),
+            record: csv::StringRecord::new(),
+            column,
+        },
+    };
+
+    Entries {
+        source,
+        options,
+        on_error,
+        offset,
+    }
+}
+
+impl<R: BufRead> Source<R> {
+    /// read the next raw field, along with its line number
+    fn next_field(&mut self) -> Result<Option<(u64, &str)>> {
+        match self {
+            Source::Lines {
+                input,
+                line,
+                number,
+            } => {
+                // reuse the same buffer for every line
+                line.clear();
+                if input.read_line(line)? == 0 {
+                    return Ok(None);
+                }
+                *number += 1;
+
+                Ok(Some((*number, line.trim_end_matches(['\r', '\n']).trim())))
+            }
+            Source::Csv {
+                reader,
+                record,
+                column,
+            } => {
+                // reuse the same record for every row
+                if !reader.read_record(record)? {
+                    return Ok(None);
+                }
+                let number = record.position().map_or(0, |p| p.line());
+
+                let field = record
+                    .get(*column)
+                    .ok_or_else(|| anyhow!("line {}: missing column {}", number, *column + 1))?;
+
+                Ok(Some((number, field.trim())))
+            }
+        }
+    }
+}
+
+impl<R: BufRead, F: Fn(&NaiveDate) -> Option<NaiveDate>> Iterator for Entries<R, F> {
+    type Item = Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let options = self.options;
+        let on_error = self.on_error;
+
+        loop {
+            let (number, field) = match self.source.next_field() {
+                Ok(Some(f)) => f,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let error = match utils::checked_date_from_str_with(field, &options) {
+                Some(date) => match (self.offset)(&date) {
+                    Some(date) => return Some(Ok(Entry::Date(date))),
+                    None => "wrong date offset for",
+                },
+                None => "invalid reference date",
+            };
+
+            match on_error {
+                OnError::Skip => continue,
+                OnError::Passthrough => return Some(Ok(Entry::Passthrough(field.to_string()))),
+                OnError::Fail => {
+                    return Some(Err(anyhow!("line {}: {} '{}'", number, error, field)))
+                }
+            }
+        }
+    }
+}
+
+/// convert a delimiter character into the single byte expected by the CSV reader
+pub fn delimiter_from_char(c: char) -> Result<u8> {
+    u8::try_from(c)
+        .ok()
+        .filter(u8::is_ascii)
+        .with_context(|| format!("delimiter '{}' is not an ASCII character", c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{Datelike, Months};
+
+    fn date(y: i32, m: u32, d: u32) -> Entry {
+        Entry::Date(NaiveDate::from_ymd_opt(y, m, d).unwrap())
+    }
+
+    fn read(input: &str, layout: Layout, on_error: OnError) -> Result<Vec<Entry>> {
+        let options = ParseOptions::default();
+        entries(input.as_bytes(), layout, options, on_error, |d| Some(*d)).collect()
+    }
+
+    #[test]
+    fn test_entries_lines_valid() {
+        let r = read(
+            "2024-04-27\n2024.04\r\n2023\n",
+            Layout::Lines,
+            OnError::Fail,
+        )
+        .unwrap();
+        assert_eq!(
+            r,
+            vec![date(2024, 4, 27), date(2024, 4, 1), date(2023, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_entries_lines_skip() {
+        let r = read("2024-04-27\nnope\n2023\n", Layout::Lines, OnError::Skip).unwrap();
+        assert_eq!(r, vec![date(2024, 4, 27), date(2023, 1, 1)]);
+    }
+
+    #[test]
+    fn test_entries_lines_passthrough() {
+        let r = read("2024-04-27\nnope\n", Layout::Lines, OnError::Passthrough).unwrap();
+        assert_eq!(
+            r,
+            vec![date(2024, 4, 27), Entry::Passthrough(String::from("nope"))]
+        );
+    }
+
+    #[test]
+    fn test_entries_lines_fail() {
+        let r = read("2024-04-27\nnope\n", Layout::Lines, OnError::Fail);
+        assert!(r.unwrap_err().to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_entries_lines_offset() {
+        // dates after 2023 cannot be shifted
+        let offset = |d: &NaiveDate| {
+            d.checked_sub_months(Months::new(12))
+                .filter(|_| d.year() < 2024)
+        };
+        let input = "2023-04-27\n2024\n2022\n".as_bytes();
+        let options = ParseOptions::default();
+
+        let r = entries(input, Layout::Lines, options, OnError::Skip, offset);
+        assert_eq!(
+            r.collect::<Result<Vec<_>>>().unwrap(),
+            vec![date(2022, 4, 27), date(2021, 1, 1)]
+        );
+
+        let r = entries(input, Layout::Lines, options, OnError::Passthrough, offset);
+        assert_eq!(
+            r.collect::<Result<Vec<_>>>().unwrap()[1],
+            Entry::Passthrough(String::from("2024"))
+        );
+
+        let r = entries(input, Layout::Lines, options, OnError::Fail, offset);
+        let e = r.collect::<Result<Vec<_>>>().unwrap_err();
+        assert_eq!(e.to_string(), "line 2: wrong date offset for '2024'");
+    }
+
+    #[test]
+    fn test_entries_csv_column() {
+        let layout = Layout::Csv {
+            column: 1,
+            delimiter: b',',
+        };
+        let r = read(
+            "a,2024-04-27\n\"b,c\",2024-05-01,x\n",
+            layout,
+            OnError::Fail,
+        )
+        .unwrap();
+        assert_eq!(r, vec![date(2024, 4, 27), date(2024, 5, 1)]);
+    }
+
+    #[test]
+    fn test_entries_csv_missing_column() {
+        let layout = Layout::Csv {
+            column: 2,
+            delimiter: b';',
+        };
+        let r = read("a;2024-04-27\n", layout, OnError::Skip);
+        assert!(r.unwrap_err().to_string().contains("missing column 3"));
+    }
+
+    #[test]
+    fn test_delimiter_from_char() {
+        assert_eq!(delimiter_from_char(';').unwrap(), b';');
+        assert!(delimiter_from_char('§').is_err());
+    }
+}
@@ -0,0 +1,354 @@
+use chrono::{Datelike, NaiveDate};
+use clap::ValueEnum;
+
+use crate::{
+    datetag::DateTag,
+    epoch::{self, Encoding},
+    hebrew::Hebrew,
+    hijri::Hijri,
+    japanese,
+    retail::RetailCalendar,
+    weeks::{self, WeekSystem},
+};
+
+/// offset between day numbers from 0001-01-01 and Julian day numbers
+const JDN_OFFSET: i64 = 1721425;
+
+/// calendar systems of date tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CalendarSystem {
+    /// gregorian years, quarters, months and weeks
+    Gregorian,
+    /// retail fiscal years, quarters, periods and weeks
+    Retail,
+    /// astronomical Julian day numbers, for daily tags
+    JulianDay,
+    /// gregorian dates with Japanese era years (e.g. R06 for 2024)
+    Japanese,
+    /// tabular Islamic calendar
+    Hijri,
+    /// Hebrew calendar, with months numbered from Tishrei
+    Hebrew,
+}
+
+/// rules used to compute the fields, periods and offsets of date tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Calendar {
+    /// numbering of gregorian weeks
+    pub weeks: WeekSystem,
+    /// calendar system of years, quarters, months and days
+    pub system: CalendarSystem,
+    /// retail fiscal calendar, used by the retail calendar system
+    pub retail: RetailCalendar,
+    /// first day of encoded tags (e.g. days since the epoch)
+    pub epoch: NaiveDate,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Calendar {
+            weeks: WeekSystem::Iso,
+            system: CalendarSystem::Gregorian,
+            retail: RetailCalendar::default(),
+            epoch: epoch::unix_epoch(),
+        }
+    }
+}
+
+impl Calendar {
+    /// value of a specifier, given its padding modifier, if it is either a
+    /// datetag extension or replaced by the calendar system
+    pub fn specifier(&self, date: &NaiveDate, pad: Option<char>, spec: char) -> Option<String> {
+        match (pad, spec) {
+            (None, 'J') => return Some(jdn(date).to_string()),
+            (None, _) => {
+                if let Some(encoding) = Encoding::from_specifier(spec) {
+                    return Some(encoding.encode(date, &self.epoch));
+                }
+            }
+            _ => {}
+        }
+
+        let value = match self.system {
+            CalendarSystem::Retail => return self.retail.specifier(date, pad, spec),
+            CalendarSystem::Japanese => japanese::specifier(date, pad, spec),
+            CalendarSystem::Hijri => month_specifier::<Hijri>(date, pad, spec),
+            CalendarSystem::Hebrew => month_specifier::<Hebrew>(date, pad, spec),
+            CalendarSystem::Gregorian | CalendarSystem::JulianDay => None,
+        };
+
+        value.or_else(|| match (pad, spec) {
+            (None, 'K') => Some(weeks::week_of_month(date).to_string()),
+            _ => self.weeks.specifier(date, pad, spec),
+        })
+    }
+
+    /// first and last day of the period of a date tag type, if it differs
+    /// from the gregorian one
+    pub fn period(&self, tag_type: &DateTag, date: &NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        match self.system {
+            CalendarSystem::Retail => self.retail.period(tag_type, date),
+            CalendarSystem::Japanese => japanese::period(tag_type, date),
+            CalendarSystem::Hijri => month_period::<Hijri>(tag_type, date),
+            CalendarSystem::Hebrew => month_period::<Hebrew>(tag_type, date),
+            CalendarSystem::Gregorian | CalendarSystem::JulianDay => None,
+        }
+    }
+
+    /// apply an offset, expressed in units of the date tag type, if the
+    /// calendar system has its own years, quarters or months
+    pub fn checked_add_offset(
+        &self,
+        date: &NaiveDate,
+        offset: i32,
+        tag_type: &DateTag,
+    ) -> Option<Option<NaiveDate>> {
+        match self.system {
+            CalendarSystem::Retail => Some(self.retail.checked_add_offset(date, offset, tag_type)),
+            CalendarSystem::Hijri => month_add_offset::<Hijri>(date, offset, tag_type),
+            CalendarSystem::Hebrew => month_add_offset::<Hebrew>(date, offset, tag_type),
+            CalendarSystem::Gregorian | CalendarSystem::JulianDay | CalendarSystem::Japanese => {
+                None
+            }
+        }
+    }
+}
+
+/// astronomical Julian day number of a date, starting at noon
+pub fn jdn(date: &NaiveDate) -> i64 {
+    date.num_days_from_ce() as i64 + JDN_OFFSET
+}
+
+/// date of an astronomical Julian day number
+pub fn date_from_jdn(jdn: i64) -> Option<NaiveDate> {
+    NaiveDate::from_num_days_from_ce_opt(i32::try_from(jdn - JDN_OFFSET).ok()?)
+}
+
+/// calendar made of years of numbered months, converted from and to
+/// gregorian dates
+pub trait MonthCalendar {
+    /// year, month and day (all starting from 1) of a gregorian date
+    fn from_date(date: &NaiveDate) -> (i32, u32, u32);
+
+    /// gregorian date of a year, month and day, if valid
+    fn to_date(year: i32, month: u32, day: u32) -> Option<NaiveDate>;
+
+    /// number of months of a year
+    fn months(year: i32) -> u32;
+
+    /// number of days of a month
+    fn month_days(year: i32, month: u32) -> u32;
+
+    /// name of a month
+    fn month_name(year: i32, month: u32) -> &'static str;
+
+    /// number of the same month in another year, for calendars whose
+    /// months vary from year to year
+    fn same_month(_from: i32, month: u32, _to: i32) -> u32 {
+        month
+    }
+}
+
+/// quarter (1-4) of a month, splitting years evenly
+fn quarter<C: MonthCalendar>(year: i32, month: u32) -> u32 {
+    (month - 1) * 4 / C::months(year) + 1
+}
+
+/// value of a year, quarter, month or day specifier in a month calendar
+fn month_specifier<C: MonthCalendar>(
+    date: &NaiveDate,
+    pad: Option<char>,
+    spec: char,
+) -> Option<String> {
+    let (year, month, day) = C::from_date(date);
+
+    let (value, width) = match spec {
+        'Y' => (year as i64, 4),
+        'y' => (year.rem_euclid(100) as i64, 2),
+        'C' => (year.div_euclid(100) as i64, 2),
+        'q' => (quarter::<C>(year, month) as i64, 1),
+        'm' => (month as i64, 2),
+        'd' | 'e' => (day as i64, 2),
+        'j' => ((*date - C::to_date(year, 1, 1)?).num_days() + 1, 3),
+        'b' | 'B' | 'h' => return Some(C::month_name(year, month).to_string()),
+        _ => return None,
+    };
+
+    Some(match (pad, spec) {
+        (Some('-'), _) => value.to_string(),
+        (Some('_'), _) | (None, 'e') => format!("{:w$}", value, w = width),
+        _ => format!("{:0w$}", value, w = width),
+    })
+}
+
+/// first and last day of the year, quarter or month of a month calendar
+fn month_period<C: MonthCalendar>(
+    tag_type: &DateTag,
+    date: &NaiveDate,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let (year, month, _) = C::from_date(date);
+
+    let (first, last) = match tag_type {
+        DateTag::Y | DateTag::Yearly => (1, C::months(year)),
+        DateTag::Q | DateTag::Quarterly => {
+            let q = quarter::<C>(year, month);
+            let months = (1..=C::months(year)).filter(|m| quarter::<C>(year, *m) == q);
+            (months.clone().min()?, months.max()?)
+        }
+        DateTag::M | DateTag::Monthly => (month, month),
+        _ => return None,
+    };
+
+    Some((
+        C::to_date(year, first, 1)?,
+        C::to_date(year, last, C::month_days(year, last))?,
+    ))
+}
+
+/// move a date by the given number of months of a month calendar, clamping
+/// its day to the end of the target month
+fn checked_add_months<C: MonthCalendar>(date: &NaiveDate, months: i64) -> Option<NaiveDate> {
+    let (mut year, mut month, day) = C::from_date(date);
+
+    let mut n = months;
+    while n > 0 {
+        let left = (C::months(year) - month) as i64;
+        if n <= left {
+            month += n as u32;
+            n = 0;
+        } else {
+            n -= left + 1;
+            year = year.checked_add(1)?;
+            month = 1;
+        }
+    }
+    while n < 0 {
+        if -n < month as i64 {
+            month -= -n as u32;
+            n = 0;
+        } else {
+            n += month as i64;
+            year = year.checked_sub(1)?;
+            month = C::months(year);
+        }
+    }
+
+    C::to_date(year, month, day.min(C::month_days(year, month)))
+}
+
+/// move a date by the given number of years of a month calendar, keeping
+/// its month and clamping its day to the end of the target month
+fn checked_add_years<C: MonthCalendar>(date: &NaiveDate, years: i32) -> Option<NaiveDate> {
+    let (year, month, day) = C::from_date(date);
+
+    let target = year.checked_add(years)?;
+    let month = C::same_month(year, month, target);
+    C::to_date(target, month, day.min(C::month_days(target, month)))
+}
+
+/// apply a year, quarter or month offset in a month calendar
+fn month_add_offset<C: MonthCalendar>(
+    date: &NaiveDate,
+    offset: i32,
+    tag_type: &DateTag,
+) -> Option<Option<NaiveDate>> {
+    match tag_type {
+        DateTag::Y | DateTag::Yearly => Some(checked_add_years::<C>(date, offset)),
+        DateTag::Q | DateTag::Quarterly => Some(checked_add_months::<C>(date, offset as i64 * 3)),
+        DateTag::M | DateTag::Monthly => Some(checked_add_months::<C>(date, offset as i64)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn calendar(system: CalendarSystem) -> Calendar {
+        Calendar {
+            system,
+            ..Calendar::default()
+        }
+    }
+
+    #[test]
+    fn test_jdn() {
+        assert_eq!(jdn(&ymd(2000, 1, 1)), 2451545);
+        assert_eq!(jdn(&ymd(2024, 4, 27)), 2460428);
+        assert_eq!(date_from_jdn(2451545), Some(ymd(2000, 1, 1)));
+    }
+
+    #[test]
+    fn test_specifier() {
+        let date = ymd(2024, 4, 27);
+        let hijri = calendar(CalendarSystem::Hijri);
+        assert_eq!(hijri.specifier(&date, None, 'Y'), Some("1445".to_string()));
+        assert_eq!(hijri.specifier(&date, None, 'm'), Some("10".to_string()));
+        assert_eq!(
+            hijri.specifier(&date, Some('-'), 'd'),
+            Some("18".to_string())
+        );
+        assert_eq!(
+            hijri.specifier(&date, None, 'B'),
+            Some("Shawwal".to_string())
+        );
+        assert_eq!(
+            hijri.specifier(&date, None, 'J'),
+            Some("2460428".to_string())
+        );
+        // weeks stay gregorian
+        assert_eq!(hijri.specifier(&date, None, 'K'), Some("4".to_string()));
+        let hebrew = calendar(CalendarSystem::Hebrew);
+        assert_eq!(hebrew.specifier(&date, None, 'm'), Some("08".to_string()));
+        assert_eq!(hebrew.specifier(&date, None, 'j'), Some("225".to_string()));
+        assert_eq!(Calendar::default().specifier(&date, None, 'Y'), None);
+    }
+
+    #[test]
+    fn test_period() {
+        let date = ymd(2024, 4, 27);
+        let hijri = calendar(CalendarSystem::Hijri);
+        assert_eq!(
+            hijri.period(&DateTag::M, &date),
+            Some((ymd(2024, 4, 10), ymd(2024, 5, 8)))
+        );
+        assert_eq!(
+            hijri.period(&DateTag::Y, &date),
+            Some((ymd(2023, 7, 19), ymd(2024, 7, 7)))
+        );
+        assert_eq!(hijri.period(&DateTag::W, &date), None);
+        let hebrew = calendar(CalendarSystem::Hebrew);
+        assert_eq!(
+            hebrew.period(&DateTag::Y, &date),
+            Some((ymd(2023, 9, 16), ymd(2024, 10, 2)))
+        );
+    }
+
+    #[test]
+    fn test_checked_add_offset() {
+        let hijri = calendar(CalendarSystem::Hijri);
+        // 30 Ramadan 1445 to 29 Shawwal 1445
+        assert_eq!(
+            hijri.checked_add_offset(&ymd(2024, 4, 9), 1, &DateTag::M),
+            Some(Some(ymd(2024, 5, 8)))
+        );
+        assert_eq!(
+            hijri.checked_add_offset(&ymd(2024, 4, 27), -10, &DateTag::M),
+            Some(Some(ymd(2023, 7, 7)))
+        );
+        let hebrew = calendar(CalendarSystem::Hebrew);
+        // 19 Nisan 5784 to 19 Nisan 5785, after a leap year
+        assert_eq!(
+            hebrew.checked_add_offset(&ymd(2024, 4, 27), 1, &DateTag::Y),
+            Some(Some(ymd(2025, 4, 17)))
+        );
+        assert_eq!(
+            hebrew.checked_add_offset(&ymd(2024, 4, 27), 1, &DateTag::D),
+            None
+        );
+    }
+}
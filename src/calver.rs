@@ -0,0 +1,365 @@
+//! CalVer release versions (e.g. `24.04.2` for the `YY.0M.MICRO` scheme)
+//!
+//! Schemes are made of the tokens described at <https://calver.org>,
+//! separated by '.', '-' or '_':
+//!  * `YYYY`, `YY`, `0Y`: full, short and zero-padded short year
+//!  * `MM`, `0M`: short and zero-padded month
+//!  * `WW`, `0W`: short and zero-padded week, along with week-based years
+//!  * `DD`, `0D`: short and zero-padded day
+//!  * `MICRO` (or `N`): release counter within the period, starting from 0
+//!
+//! The shortest period of the date tokens (i.e. day, week, month or year)
+//! drives the reset of the release counter.
+
+use std::{
+    fs,
+    io::{self, BufRead},
+    path::PathBuf,
+    process::Command,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::NaiveDate;
+use regex::Regex;
+
+use crate::{datetag::DateTag, locale::DateFormatter};
+
+/// arguments of the calver subcommand
+#[derive(Debug, clap::Args)]
+pub struct CalverArgs {
+    /// CalVer scheme (e.g. 'YY.0M.MICRO' or 'YYYY.0M.0D.N')
+    scheme: String,
+
+    /// Reference date, using today if not specified
+    date: Option<String>,
+
+    /// Read existing versions from a file, one per line
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["stdin", "git"])]
+    from: Option<PathBuf>,
+
+    /// Read existing versions from the standard input, one per line
+    #[arg(long, conflicts_with = "git")]
+    stdin: bool,
+
+    /// Read existing versions from the tags of the local git repository
+    #[arg(long)]
+    git: bool,
+
+    /// Version prefix (e.g. 'v'), versions without it are ignored
+    #[arg(long, default_value = "")]
+    prefix: String,
+}
+
+/// element of a CalVer scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    FullYear,
+    ShortYear,
+    ZeroPaddedYear,
+    ShortMonth,
+    ZeroPaddedMonth,
+    ShortWeek,
+    ZeroPaddedWeek,
+    ShortDay,
+    ZeroPaddedDay,
+    Micro,
+    Separator(char),
+}
+
+impl Token {
+    fn parse(s: &str) -> Result<Self> {
+        Ok(match s {
+            "YYYY" => Token::FullYear,
+            "YY" => Token::ShortYear,
+            "0Y" => Token::ZeroPaddedYear,
+            "MM" => Token::ShortMonth,
+            "0M" => Token::ZeroPaddedMonth,
+            "WW" => Token::ShortWeek,
+            "0W" => Token::ZeroPaddedWeek,
+            "DD" => Token::ShortDay,
+            "0D" => Token::ZeroPaddedDay,
+            "MICRO" | "N" => Token::Micro,
+            _ => bail!("unknown CalVer token '{}'", s),
+        })
+    }
+
+    /// date format of the token, years being week-based along with weeks
+    fn format(&self, weekly: bool) -> String {
+        match (self, weekly) {
+            (Token::FullYear, false) => "%Y".to_string(),
+            (Token::FullYear, true) => "%G".to_string(),
+            (Token::ShortYear, false) => "%-y".to_string(),
+            (Token::ShortYear, true) => "%-g".to_string(),
+            (Token::ZeroPaddedYear, false) => "%y".to_string(),
+            (Token::ZeroPaddedYear, true) => "%g".to_string(),
+            (Token::ShortMonth, _) => "%-m".to_string(),
+            (Token::ZeroPaddedMonth, _) => "%m".to_string(),
+            (Token::ShortWeek, _) => "%-V".to_string(),
+            (Token::ZeroPaddedWeek, _) => "%V".to_string(),
+            (Token::ShortDay, _) => "%-d".to_string(),
+            (Token::ZeroPaddedDay, _) => "%d".to_string(),
+            (Token::Micro, _) => String::new(),
+            (Token::Separator(c), _) => c.to_string().replace('%', "%%"),
+        }
+    }
+
+    /// pattern matching the token in existing versions
+    fn pattern(&self) -> String {
+        match self {
+            Token::FullYear => r"(\d{4})".to_string(),
+            Token::ShortYear => r"(\d{1,3})".to_string(),
+            Token::ZeroPaddedYear => r"(\d{2,3})".to_string(),
+            Token::ShortMonth | Token::ShortWeek | Token::ShortDay => r"(\d{1,2})".to_string(),
+            Token::ZeroPaddedMonth | Token::ZeroPaddedWeek | Token::ZeroPaddedDay => {
+                r"(\d{2})".to_string()
+            }
+            Token::Micro => r"(\d+)".to_string(),
+            Token::Separator(c) => regex::escape(&c.to_string()),
+        }
+    }
+}
+
+/// CalVer scheme, as a sequence of tokens and separators
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scheme {
+    tokens: Vec<Token>,
+}
+
+impl Scheme {
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut tokens = Vec::new();
+        let mut start = 0;
+
+        for (i, c) in s.char_indices().filter(|(_, c)| ".-_".contains(*c)) {
+            tokens.push(Token::parse(&s[start..i])?);
+            tokens.push(Token::Separator(c));
+            start = i + c.len_utf8();
+        }
+        tokens.push(Token::parse(&s[start..])?);
+
+        if tokens.iter().filter(|t| **t == Token::Micro).count() > 1 {
+            bail!("more than one MICRO token");
+        }
+        if !tokens
+            .iter()
+            .any(|t| !matches!(t, Token::Micro | Token::Separator(_)))
+        {
+            bail!("no date token");
+        }
+
+        Ok(Scheme { tokens })
+    }
+
+    /// tag type of the shortest period of the date tokens
+    pub fn tag_type(&self) -> DateTag {
+        let has = |tokens: &[Token]| self.tokens.iter().any(|t| tokens.contains(t));
+
+        if has(&[Token::ShortDay, Token::ZeroPaddedDay]) {
+            DateTag::D
+        } else if has(&[Token::ShortWeek, Token::ZeroPaddedWeek]) {
+            DateTag::W
+        } else if has(&[Token::ShortMonth, Token::ZeroPaddedMonth]) {
+            DateTag::M
+        } else {
+            DateTag::Y
+        }
+    }
+
+    fn has_micro(&self) -> bool {
+        self.tokens.contains(&Token::Micro)
+    }
+
+    /// render the version of a date with the given release counter
+    pub fn render(&self, date: &NaiveDate, micro: u64, formatter: &DateFormatter) -> String {
+        let weekly = self.tag_type() == DateTag::W;
+
+        self.tokens
+            .iter()
+            .map(|t| match t {
+                Token::Micro => micro.to_string(),
+                _ => formatter.format(date, &t.format(weekly)),
+            })
+            .collect()
+    }
+
+    /// regex matching versions of the scheme, capturing the release counter
+    fn regex(&self) -> Regex {
+        let pattern: String = self.tokens.iter().map(Token::pattern).collect();
+        Regex::new(&format!("^{}$", pattern)).unwrap()
+    }
+
+    /// next version of the period of a date, following the given existing
+    /// versions
+    ///
+    /// Versions of other periods, or not following the scheme, are ignored.
+    pub fn next(
+        &self,
+        date: &NaiveDate,
+        versions: &[String],
+        formatter: &DateFormatter,
+    ) -> Result<String> {
+        let regex = self.regex();
+        let period = self.render(date, 0, formatter);
+
+        // existing versions of the same period, along with their counter
+        let micros: Vec<u64> = versions
+            .iter()
+            .filter_map(|v| {
+                let caps = regex.captures(v.trim())?;
+                let micro = match self.tokens.iter().position(|t| *t == Token::Micro) {
+                    Some(index) => {
+                        // capture groups only belong to tokens, not separators
+                        let group = self.tokens[..index]
+                            .iter()
+                            .filter(|t| !matches!(t, Token::Separator(_)))
+                            .count();
+                        caps[group + 1].parse().ok()?
+                    }
+                    None => 0,
+                };
+                (self.render(date, micro, formatter) == v.trim()).then_some(micro)
+            })
+            .collect();
+
+        match micros.iter().max() {
+            None => Ok(period),
+            Some(micro) if self.has_micro() => Ok(self.render(date, micro + 1, formatter)),
+            Some(_) => bail!(
+                "version '{}' already exists, add a MICRO token to the scheme",
+                period
+            ),
+        }
+    }
+}
+
+/// read existing versions, keeping the ones with the given prefix, without it
+fn versions(args: &CalverArgs) -> Result<Vec<String>> {
+    let lines: Vec<String> = if let Some(file) = &args.from {
+        fs::read_to_string(file)
+            .with_context(|| format!("cannot read versions from '{}'", file.display()))?
+            .lines()
+            .map(str::to_string)
+            .collect()
+    } else if args.stdin {
+        io::stdin().lock().lines().collect::<io::Result<_>>()?
+    } else if args.git {
+        let output = Command::new("git")
+            .args(["tag", "--list"])
+            .output()
+            .context("unable to retrieve git tags")?;
+        if !output.status.success() {
+            bail!(
+                "unable to retrieve git tags: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        String::from_utf8(output.stdout)?
+            .lines()
+            .map(str::to_string)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(lines
+        .iter()
+        .filter_map(|l| l.trim().strip_prefix(args.prefix.as_str()))
+        .map(str::to_string)
+        .collect())
+}
+
+/// next version of the reference date, along with its prefix
+pub fn run(
+    args: &CalverArgs,
+    parse: impl Fn(&str) -> Result<NaiveDate>,
+    today: NaiveDate,
+    formatter: &DateFormatter,
+) -> Result<String> {
+    let scheme = Scheme::parse(&args.scheme)
+        .map_err(|e| anyhow!("wrong CalVer scheme '{}': {}", args.scheme, e))?;
+    let date = args
+        .date
+        .as_deref()
+        .map(parse)
+        .transpose()?
+        .unwrap_or(today);
+
+    let version = scheme.next(&date, &versions(args)?, formatter)?;
+    Ok(format!("{}{}", args.prefix, version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::Calendar;
+    use chrono::Locale;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn formatter() -> DateFormatter {
+        DateFormatter {
+            locale: Locale::POSIX,
+            calendar: Calendar::default(),
+        }
+    }
+
+    fn next(scheme: &str, date: NaiveDate, versions: &[&str]) -> Result<String> {
+        let versions: Vec<String> = versions.iter().map(|v| v.to_string()).collect();
+        Scheme::parse(scheme)?.next(&date, &versions, &formatter())
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Scheme::parse("YY.0M.MICRO").unwrap().tag_type(), DateTag::M);
+        assert_eq!(
+            Scheme::parse("YYYY.MM.DD.N").unwrap().tag_type(),
+            DateTag::D
+        );
+        assert_eq!(Scheme::parse("YYYY-0W").unwrap().tag_type(), DateTag::W);
+        assert_eq!(Scheme::parse("YYYY_MICRO").unwrap().tag_type(), DateTag::Y);
+        assert!(Scheme::parse("YY.0M.PATCH").is_err());
+        assert!(Scheme::parse("YY..MICRO").is_err());
+        assert!(Scheme::parse("MICRO").is_err());
+        assert!(Scheme::parse("YY.MICRO.N").is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let s = Scheme::parse("YY.0M.MICRO").unwrap();
+        assert_eq!(s.render(&ymd(2024, 4, 27), 3, &formatter()), "24.04.3");
+        let s = Scheme::parse("YYYY.MM.DD").unwrap();
+        assert_eq!(s.render(&ymd(2024, 4, 7), 0, &formatter()), "2024.4.7");
+        // week-based years
+        let s = Scheme::parse("0Y.0W").unwrap();
+        assert_eq!(s.render(&ymd(2024, 12, 30), 0, &formatter()), "25.01");
+    }
+
+    #[test]
+    fn test_next() {
+        let date = ymd(2024, 4, 27);
+        assert_eq!(next("YY.0M.MICRO", date, &[]).unwrap(), "24.04.0");
+        assert_eq!(
+            next("YY.0M.MICRO", date, &["24.03.0", "24.04.0", "24.04.1"]).unwrap(),
+            "24.04.2"
+        );
+        // the counter is reset with a new period
+        assert_eq!(
+            next("YY.0M.MICRO", date, &["24.03.0", "24.03.1"]).unwrap(),
+            "24.04.0"
+        );
+        // versions of other schemes are ignored
+        assert_eq!(
+            next("YYYY.0M.0D.N", date, &["24.04.5", "2024.04.27.0", "foo"]).unwrap(),
+            "2024.04.27.1"
+        );
+    }
+
+    #[test]
+    fn test_next_without_micro() {
+        let date = ymd(2024, 4, 27);
+        assert_eq!(next("YYYY.0M", date, &["2024.03"]).unwrap(), "2024.04");
+        assert!(next("YYYY.0M", date, &["2024.04"]).is_err());
+    }
+}
@@ -1,15 +1,415 @@
+use std::fmt;
+
 use clap::ValueEnum;
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
-pub enum DateStyle {
+/// named date tag styles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StylePreset {
     /// yyyymmdd
     Plain,
     /// yyyy.mm.dd
     Dot,
-    /// yyyy/mm/dd
+    /// yyyy/mm/dd, also used for nested directories
     Slash,
     /// yyyy:mm:dd
     Colon,
     /// yyyy-mm-dd
     Dash,
+    /// yyyy_mm_dd
+    Underscore,
+    /// yyyy mm dd
+    Space,
+    /// dd.mm.yyyy
+    European,
+    /// mm/dd/yyyy
+    Us,
+    /// yymmdd
+    Compact,
+    /// dd-mmm-yyyy, abbreviated month names (e.g. 27-Apr-2024)
+    MonthAbbr,
+    /// dd-mmmm-yyyy, full month names (e.g. 27-April-2024)
+    MonthName,
+    /// year=yyyy/month=mm/day=dd, Hive partition paths
+    Hive,
+    /// yyyy-Www-d, ISO 8601 week dates (e.g. 2024-W17-6)
+    IsoWeek,
+    /// yyyyWwwd, ISO 8601 basic week dates (e.g. 2024W176)
+    IsoWeekCompact,
+    /// FYyy-Pmm-Ww, fiscal designators (e.g. FY24-P04-W2)
+    Retail,
+}
+
+/// order of date fields in a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FieldOrder {
+    /// year, month, day
+    Ymd,
+    /// day, month, year
+    Dmy,
+    /// month, day, year
+    Mdy,
+}
+
+/// representation of months in a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MonthRepr {
+    /// month number (e.g. 04)
+    Number,
+    /// abbreviated month name (e.g. Apr)
+    Abbr,
+    /// full month name (e.g. April)
+    Name,
+}
+
+/// single date field of a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Field {
+    Year,
+    Quarter,
+    Month,
+    Day,
+    #[value(skip)]
+    IsoYear,
+    #[value(name = "week")]
+    IsoWeek,
+    Weekday,
+    DayOfYear,
+    WeekOfMonth,
+}
+
+/// key names of Hive partition paths (e.g. 'year=2024/month=04')
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HiveKeys {
+    pub year: String,
+    pub quarter: String,
+    pub month: String,
+    pub week: String,
+    pub day: String,
+    pub weekday: String,
+    pub day_of_year: String,
+    pub week_of_month: String,
+}
+
+impl Default for HiveKeys {
+    fn default() -> Self {
+        HiveKeys {
+            year: String::from("year"),
+            quarter: String::from("quarter"),
+            month: String::from("month"),
+            week: String::from("week"),
+            day: String::from("day"),
+            weekday: String::from("weekday"),
+            day_of_year: String::from("day_of_year"),
+            week_of_month: String::from("week_of_month"),
+        }
+    }
+}
+
+impl HiveKeys {
+    /// key name of a date field
+    pub fn get(&self, field: Field) -> &str {
+        match field {
+            Field::Year | Field::IsoYear => &self.year,
+            Field::Quarter => &self.quarter,
+            Field::Month => &self.month,
+            Field::IsoWeek => &self.week,
+            Field::Day => &self.day,
+            Field::Weekday => &self.weekday,
+            Field::DayOfYear => &self.day_of_year,
+            Field::WeekOfMonth => &self.week_of_month,
+        }
+    }
+
+    /// set the key name of a date field
+    pub fn set(&mut self, field: Field, key: String) {
+        match field {
+            Field::Year | Field::IsoYear => self.year = key,
+            Field::Quarter => self.quarter = key,
+            Field::Month => self.month = key,
+            Field::IsoWeek => self.week = key,
+            Field::Day => self.day = key,
+            Field::Weekday => self.weekday = key,
+            Field::DayOfYear => self.day_of_year = key,
+            Field::WeekOfMonth => self.week_of_month = key,
+        }
+    }
+}
+
+/// convert a 'FIELD=KEY' string (e.g. 'month=mo') into a Hive key name
+pub fn try_hive_key_from_str(s: &str) -> Result<(Field, String), String> {
+    let (field, key) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected 'FIELD=KEY', found '{}'", s))?;
+
+    if key.is_empty() || key.contains(['=', '/']) {
+        return Err(format!("invalid key name '{}'", key));
+    }
+
+    Ok((Field::from_str(field, true)?, String::from(key)))
+}
+
+/// date tag style, made of a field separator and a field order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateStyle {
+    pub separator: String,
+    pub order: FieldOrder,
+    /// use two-digit years (e.g. 240427)
+    pub short_year: bool,
+    /// representation of months
+    pub month: MonthRepr,
+    /// label each field with a key, as in Hive partition paths
+    pub hive: Option<HiveKeys>,
+    /// prefix week numbers with the ISO 8601 designator (e.g. W17)
+    pub week_designator: bool,
+    /// prefix years with 'FY' and months (periods) with 'P' (e.g. FY24-P04)
+    pub fiscal: bool,
+}
+
+impl From<StylePreset> for DateStyle {
+    fn from(preset: StylePreset) -> Self {
+        let (separator, order) = match preset {
+            StylePreset::Plain => ("", FieldOrder::Ymd),
+            StylePreset::Dot => (".", FieldOrder::Ymd),
+            StylePreset::Slash => ("/", FieldOrder::Ymd),
+            StylePreset::Colon => (":", FieldOrder::Ymd),
+            StylePreset::Dash => ("-", FieldOrder::Ymd),
+            StylePreset::Underscore => ("_", FieldOrder::Ymd),
+            StylePreset::Space => (" ", FieldOrder::Ymd),
+            StylePreset::European => (".", FieldOrder::Dmy),
+            StylePreset::Us => ("/", FieldOrder::Mdy),
+            StylePreset::Compact => ("", FieldOrder::Ymd),
+            StylePreset::MonthAbbr | StylePreset::MonthName => ("-", FieldOrder::Dmy),
+            StylePreset::Hive => ("/", FieldOrder::Ymd),
+            StylePreset::IsoWeek => ("-", FieldOrder::Ymd),
+            StylePreset::IsoWeekCompact => ("", FieldOrder::Ymd),
+            StylePreset::Retail => ("-", FieldOrder::Ymd),
+        };
+
+        let month = match preset {
+            StylePreset::MonthAbbr => MonthRepr::Abbr,
+            StylePreset::MonthName => MonthRepr::Name,
+            _ => MonthRepr::Number,
+        };
+
+        DateStyle {
+            separator: String::from(separator),
+            order,
+            short_year: matches!(preset, StylePreset::Compact | StylePreset::Retail),
+            month,
+            hive: (preset == StylePreset::Hive).then(HiveKeys::default),
+            week_designator: matches!(
+                preset,
+                StylePreset::IsoWeek | StylePreset::IsoWeekCompact | StylePreset::Retail
+            ),
+            fiscal: preset == StylePreset::Retail,
+        }
+    }
+}
+
+impl DateStyle {
+    /// build a format string from fields given in year-month-day order
+    pub fn pattern(&self, fields: &[Field]) -> String {
+        let mut fields = fields.to_vec();
+
+        match self.order {
+            FieldOrder::Ymd => {}
+            FieldOrder::Dmy => fields.reverse(),
+            // move the year field (if any) to the end
+            FieldOrder::Mdy if !fields.is_empty() => fields.rotate_left(1),
+            FieldOrder::Mdy => {}
+        }
+
+        fields
+            .iter()
+            .map(|f| {
+                let spec = match (f, self.short_year) {
+                    (Field::Year, false) => "%Y",
+                    (Field::Year, true) => "%y",
+                    (Field::Quarter, _) => "%q",
+                    (Field::Month, _) => match self.month {
+                        MonthRepr::Number => "%m",
+                        MonthRepr::Abbr => "%b",
+                        MonthRepr::Name => "%B",
+                    },
+                    (Field::Day, _) => "%d",
+                    (Field::IsoYear, false) => "%G",
+                    (Field::IsoYear, true) => "%g",
+                    (Field::IsoWeek, _) => "%V",
+                    (Field::Weekday, _) => "%u",
+                    (Field::DayOfYear, _) => "%j",
+                    (Field::WeekOfMonth, _) => "%K",
+                };
+
+                let designator = match f {
+                    Field::Quarter => "Q",
+                    Field::WeekOfMonth => "W",
+                    Field::IsoWeek if self.week_designator => "W",
+                    Field::Year | Field::IsoYear if self.fiscal => "FY",
+                    Field::Month if self.fiscal && self.month == MonthRepr::Number => "P",
+                    _ => "",
+                };
+
+                match &self.hive {
+                    Some(keys) => format!("{}={}", escape(keys.get(*f)), spec),
+                    None => format!("{}{}", designator, spec),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(&escape(&self.separator))
+    }
+}
+
+/// escape a literal string to be used in a format string
+fn escape(s: &str) -> String {
+    s.replace('%', "%%")
+}
+
+/// display the preset name, if any, otherwise 'custom'
+impl fmt::Display for DateStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let preset = StylePreset::value_variants()
+            .iter()
+            .find(|p| DateStyle::from(**p) == *self)
+            .and_then(|p| p.to_possible_value());
+
+        match preset {
+            Some(p) => write!(f, "{}", p.get_name()),
+            None => write!(f, "custom"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(separator: &str, order: FieldOrder) -> DateStyle {
+        DateStyle {
+            separator: String::from(separator),
+            order,
+            ..StylePreset::Plain.into()
+        }
+    }
+
+    #[test]
+    fn test_pattern_orders() {
+        let fields = [Field::Year, Field::Month, Field::Day];
+        assert_eq!(style("-", FieldOrder::Ymd).pattern(&fields), "%Y-%m-%d");
+        assert_eq!(style(".", FieldOrder::Dmy).pattern(&fields), "%d.%m.%Y");
+        assert_eq!(style("/", FieldOrder::Mdy).pattern(&fields), "%m/%d/%Y");
+    }
+
+    #[test]
+    fn test_pattern_partial_fields() {
+        let fields = [Field::Year, Field::Month];
+        assert_eq!(style("/", FieldOrder::Mdy).pattern(&fields), "%m/%Y");
+        assert_eq!(style("", FieldOrder::Dmy).pattern(&fields), "%m%Y");
+        assert_eq!(style("", FieldOrder::Dmy).pattern(&[Field::Year]), "%Y");
+    }
+
+    #[test]
+    fn test_pattern_escaped_separator() {
+        let fields = [Field::Year, Field::Month];
+        assert_eq!(style("%", FieldOrder::Ymd).pattern(&fields), "%Y%%%m");
+    }
+
+    #[test]
+    fn test_pattern_short_year() {
+        let mut s = style("", FieldOrder::Ymd);
+        s.short_year = true;
+        assert_eq!(
+            s.pattern(&[Field::Year, Field::Month, Field::Day]),
+            "%y%m%d"
+        );
+        assert_eq!(s.pattern(&[Field::IsoYear, Field::IsoWeek]), "%g%V");
+        s.order = FieldOrder::Dmy;
+        assert_eq!(s.pattern(&[Field::Year, Field::Month]), "%m%y");
+    }
+
+    #[test]
+    fn test_pattern_month_names() {
+        let fields = [Field::Year, Field::Month, Field::Day];
+        let s = DateStyle::from(StylePreset::MonthAbbr);
+        assert_eq!(s.pattern(&fields), "%d-%b-%Y");
+        let s = DateStyle::from(StylePreset::MonthName);
+        assert_eq!(s.pattern(&fields[..2]), "%B-%Y");
+    }
+
+    #[test]
+    fn test_pattern_quarter() {
+        let fields = [Field::Year, Field::Quarter];
+        assert_eq!(style("-", FieldOrder::Ymd).pattern(&fields), "%Y-Q%q");
+        assert_eq!(style("", FieldOrder::Dmy).pattern(&fields), "Q%q%Y");
+    }
+
+    #[test]
+    fn test_pattern_hive() {
+        let mut s = DateStyle::from(StylePreset::Hive);
+        let fields = [Field::Year, Field::Month, Field::Day];
+        assert_eq!(s.pattern(&fields), "year=%Y/month=%m/day=%d");
+        let fields = [Field::Year, Field::Quarter];
+        assert_eq!(s.pattern(&fields), "year=%Y/quarter=%q");
+        let fields = [Field::IsoYear, Field::IsoWeek];
+        assert_eq!(s.pattern(&fields), "year=%G/week=%V");
+
+        let keys = s.hive.as_mut().unwrap();
+        keys.set(Field::Year, String::from("yr%"));
+        keys.set(Field::IsoWeek, String::from("wk"));
+        assert_eq!(s.pattern(&fields), "yr%%=%G/wk=%V");
+    }
+
+    #[test]
+    fn test_pattern_iso_week() {
+        let fields = [Field::IsoYear, Field::IsoWeek, Field::Weekday];
+        let s = DateStyle::from(StylePreset::IsoWeek);
+        assert_eq!(s.pattern(&fields), "%G-W%V-%u");
+        assert_eq!(s.pattern(&fields[..2]), "%G-W%V");
+        let s = DateStyle::from(StylePreset::IsoWeekCompact);
+        assert_eq!(s.pattern(&fields), "%GW%V%u");
+        assert_eq!(style("-", FieldOrder::Ymd).pattern(&fields), "%G-%V-%u");
+    }
+
+    #[test]
+    fn test_pattern_ordinal_and_week_of_month() {
+        let s = style("-", FieldOrder::Ymd);
+        assert_eq!(s.pattern(&[Field::Year, Field::DayOfYear]), "%Y-%j");
+        let fields = [Field::Year, Field::Month, Field::WeekOfMonth];
+        assert_eq!(s.pattern(&fields), "%Y-%m-W%K");
+        let s = DateStyle::from(StylePreset::Hive);
+        assert_eq!(s.pattern(&fields), "year=%Y/month=%m/week_of_month=%K");
+    }
+
+    #[test]
+    fn test_pattern_retail() {
+        let s = DateStyle::from(StylePreset::Retail);
+        let fields = [Field::Year, Field::Month, Field::WeekOfMonth];
+        assert_eq!(s.pattern(&fields), "FY%y-P%m-W%K");
+        assert_eq!(s.pattern(&[Field::IsoYear, Field::IsoWeek]), "FY%g-W%V");
+        assert_eq!(s.pattern(&[Field::Year, Field::Quarter]), "FY%y-Q%q");
+    }
+
+    #[test]
+    fn test_try_hive_key_from_str() {
+        let (f, k) = try_hive_key_from_str("month=mo").unwrap();
+        assert_eq!((f, k.as_str()), (Field::Month, "mo"));
+        let (f, _) = try_hive_key_from_str("week=wk").unwrap();
+        assert_eq!(f, Field::IsoWeek);
+        assert!(try_hive_key_from_str("month").is_err());
+        assert!(try_hive_key_from_str("month=").is_err());
+        assert!(try_hive_key_from_str("hour=h").is_err());
+        assert!(try_hive_key_from_str("day=a/b").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(DateStyle::from(StylePreset::Us).to_string(), "us");
+        assert_eq!(style("_", FieldOrder::Ymd).to_string(), "underscore");
+        assert_eq!(style("_", FieldOrder::Dmy).to_string(), "custom");
+        assert_eq!(DateStyle::from(StylePreset::Compact).to_string(), "compact");
+        assert_eq!(DateStyle::from(StylePreset::Hive).to_string(), "hive");
+        assert_eq!(
+            DateStyle::from(StylePreset::IsoWeek).to_string(),
+            "iso-week"
+        );
+    }
 }
@@ -1,71 +1,152 @@
+use chrono::{Datelike, Months, NaiveDate};
 use clap::ValueEnum;
 
-use crate::datestyle::DateStyle;
+use crate::{
+    calendar::Calendar,
+    datestyle::{DateStyle, Field},
+    epoch::Encoding,
+    weeks,
+};
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum DateTag {
     Y,
     /// yearly tags (e.g. 2022)
     Yearly,
+    Q,
+    /// quarterly tags (e.g. 2022Q4)
+    Quarterly,
     W,
     /// weekly ISO 8601 tags (e.g. 202234)
     Weekly,
+    Wd,
+    /// week-day ISO 8601 tags (e.g. 2022346)
+    WeekDay,
     M,
     /// monthly tags (e.g. 202212)
     Monthly,
     D,
     /// daily tags (e.g. 20221230)
     Daily,
+    O,
+    /// ordinal daily tags, using the day of the year (e.g. 2022364)
+    Ordinal,
+    Mw,
+    /// week of the month tags, days 1-7 being week 1 (e.g. 202212W5)
+    WeekOfMonth,
+    Ed,
+    /// days since the epoch (e.g. 19356)
+    EpochDay,
+    Es,
+    /// seconds since the epoch, at midnight UTC (e.g. 1672358400)
+    EpochSeconds,
+    B36,
+    /// base 36 days since the epoch (e.g. exo)
+    Base36,
+    X,
+    /// hexadecimal days since the epoch (e.g. 4b9c)
+    Hex,
 }
 
 /// associate a specific string format to each value
 impl DateTag {
-    pub fn get_format(&self, style: DateStyle) -> &str {
-        match (self, style) {
-            // Yealy tags
-            (DateTag::Y, DateStyle::Plain) => "%Y",
-            (DateTag::Y, DateStyle::Dot) => "%Y",
-            (DateTag::Y, DateStyle::Slash) => "%Y",
-            (DateTag::Y, DateStyle::Colon) => "%Y",
-            (DateTag::Y, DateStyle::Dash) => "%Y",
-            (DateTag::Yearly, DateStyle::Plain) => "%Y",
-            (DateTag::Yearly, DateStyle::Dot) => "%Y",
-            (DateTag::Yearly, DateStyle::Slash) => "%Y",
-            (DateTag::Yearly, DateStyle::Colon) => "%Y",
-            (DateTag::Yearly, DateStyle::Dash) => "%Y",
-            // Monthly tags
-            (DateTag::M, DateStyle::Plain) => "%Y%m",
-            (DateTag::M, DateStyle::Dot) => "%Y.%m",
-            (DateTag::M, DateStyle::Slash) => "%Y/%m",
-            (DateTag::M, DateStyle::Colon) => "%Y:%m",
-            (DateTag::M, DateStyle::Dash) => "%Y-%m",
-            (DateTag::Monthly, DateStyle::Plain) => "%Y%m",
-            (DateTag::Monthly, DateStyle::Dot) => "%Y.%m",
-            (DateTag::Monthly, DateStyle::Slash) => "%Y/%m",
-            (DateTag::Monthly, DateStyle::Colon) => "%Y:%m",
-            (DateTag::Monthly, DateStyle::Dash) => "%Y-%m",
-            // Weekly tags
-            (DateTag::W, DateStyle::Plain) => "%G%V",
-            (DateTag::W, DateStyle::Dot) => "%G.%V",
-            (DateTag::W, DateStyle::Slash) => "%G/%V",
-            (DateTag::W, DateStyle::Colon) => "%G:%V",
-            (DateTag::W, DateStyle::Dash) => "%G-%V",
-            (DateTag::Weekly, DateStyle::Plain) => "%G%V",
-            (DateTag::Weekly, DateStyle::Dot) => "%G.%V",
-            (DateTag::Weekly, DateStyle::Slash) => "%G/%V",
-            (DateTag::Weekly, DateStyle::Colon) => "%G:%V",
-            (DateTag::Weekly, DateStyle::Dash) => "%G-%V",
-            // Dayly tags
-            (DateTag::D, DateStyle::Plain) => "%Y%m%d",
-            (DateTag::D, DateStyle::Dot) => "%Y.%m.%d",
-            (DateTag::D, DateStyle::Slash) => "%Y/%m/%d",
-            (DateTag::D, DateStyle::Colon) => "%Y:%m:%d",
-            (DateTag::D, DateStyle::Dash) => "%Y-%m-%d",
-            (DateTag::Daily, DateStyle::Plain) => "%Y%m%d",
-            (DateTag::Daily, DateStyle::Dot) => "%Y.%m.%d",
-            (DateTag::Daily, DateStyle::Slash) => "%Y/%m/%d",
-            (DateTag::Daily, DateStyle::Colon) => "%Y:%m:%d",
-            (DateTag::Daily, DateStyle::Dash) => "%Y-%m-%d",
+    /// date fields of the tag, in year-month-day order
+    pub fn fields(&self) -> &'static [Field] {
+        match self {
+            DateTag::Y | DateTag::Yearly => &[Field::Year],
+            DateTag::Q | DateTag::Quarterly => &[Field::Year, Field::Quarter],
+            DateTag::W | DateTag::Weekly => &[Field::IsoYear, Field::IsoWeek],
+            DateTag::Wd | DateTag::WeekDay => &[Field::IsoYear, Field::IsoWeek, Field::Weekday],
+            DateTag::M | DateTag::Monthly => &[Field::Year, Field::Month],
+            DateTag::D | DateTag::Daily => &[Field::Year, Field::Month, Field::Day],
+            DateTag::O | DateTag::Ordinal => &[Field::Year, Field::DayOfYear],
+            DateTag::Mw | DateTag::WeekOfMonth => &[Field::Year, Field::Month, Field::WeekOfMonth],
+            // encoded tags have no date fields
+            DateTag::Ed
+            | DateTag::EpochDay
+            | DateTag::Es
+            | DateTag::EpochSeconds
+            | DateTag::B36
+            | DateTag::Base36
+            | DateTag::X
+            | DateTag::Hex => &[],
+        }
+    }
+
+    /// encoding of the tag, counting days or seconds since an epoch
+    pub fn encoding(&self) -> Option<Encoding> {
+        match self {
+            DateTag::Ed | DateTag::EpochDay => Some(Encoding::Days),
+            DateTag::Es | DateTag::EpochSeconds => Some(Encoding::Seconds),
+            DateTag::B36 | DateTag::Base36 => Some(Encoding::Base36),
+            DateTag::X | DateTag::Hex => Some(Encoding::Hex),
+            _ => None,
+        }
+    }
+
+    pub fn get_format(&self, style: &DateStyle) -> String {
+        match self.encoding() {
+            Some(encoding) => encoding.specifier().to_string(),
+            None => style.pattern(self.fields()),
+        }
+    }
+
+    /// canonical name of the tag type, regardless of the short alias used
+    pub fn name(&self) -> &'static str {
+        match self {
+            DateTag::Y | DateTag::Yearly => "yearly",
+            DateTag::Q | DateTag::Quarterly => "quarterly",
+            DateTag::W | DateTag::Weekly => "weekly",
+            DateTag::Wd | DateTag::WeekDay => "week-day",
+            DateTag::M | DateTag::Monthly => "monthly",
+            DateTag::D | DateTag::Daily => "daily",
+            DateTag::O | DateTag::Ordinal => "ordinal",
+            DateTag::Mw | DateTag::WeekOfMonth => "week-of-month",
+            DateTag::Ed | DateTag::EpochDay => "epoch-day",
+            DateTag::Es | DateTag::EpochSeconds => "epoch-seconds",
+            DateTag::B36 | DateTag::Base36 => "base36",
+            DateTag::X | DateTag::Hex => "hex",
+        }
+    }
+
+    /// first and last day of the period containing the given date, if
+    /// within the supported range of dates
+    pub fn period(&self, date: &NaiveDate, calendar: &Calendar) -> Option<(NaiveDate, NaiveDate)> {
+        if let Some(period) = calendar.period(self, date) {
+            return Some(period);
+        }
+
+        match self {
+            DateTag::Y | DateTag::Yearly => Some((
+                NaiveDate::from_yo_opt(date.year(), 1)?,
+                NaiveDate::from_ymd_opt(date.year(), 12, 31)?,
+            )),
+            DateTag::Q | DateTag::Quarterly => {
+                let first = NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1)?;
+                let last = first.checked_add_months(Months::new(3))?.pred_opt()?;
+                Some((first, last))
+            }
+            DateTag::W | DateTag::Weekly => calendar.weeks.period(date),
+            DateTag::M | DateTag::Monthly => {
+                let first = date.with_day(1)?;
+                let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+                Some((first, last))
+            }
+            DateTag::Mw | DateTag::WeekOfMonth => Some(weeks::month_week_period(date)),
+            DateTag::Wd
+            | DateTag::WeekDay
+            | DateTag::D
+            | DateTag::Daily
+            | DateTag::O
+            | DateTag::Ordinal
+            | DateTag::Ed
+            | DateTag::EpochDay
+            | DateTag::Es
+            | DateTag::EpochSeconds
+            | DateTag::B36
+            | DateTag::Base36
+            | DateTag::X
+            | DateTag::Hex => Some((*date, *date)),
         }
     }
 }
@@ -73,184 +154,372 @@ impl DateTag {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::datestyle::{FieldOrder, MonthRepr, StylePreset};
+    use crate::weeks::WeekSystem;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
 
     #[test]
     fn test_get_format_year_plain() {
         // test year-related variants
         let d = DateTag::Yearly;
-        assert!(d.get_format(DateStyle::Plain) == "%Y");
+        assert!(d.get_format(&StylePreset::Plain.into()) == "%Y");
         let d = DateTag::Y;
-        assert!(d.get_format(DateStyle::Plain) == "%Y");
+        assert!(d.get_format(&StylePreset::Plain.into()) == "%Y");
     }
 
     #[test]
     fn test_get_format_year_dot() {
         // test year-related variants
         let d = DateTag::Yearly;
-        assert!(d.get_format(DateStyle::Dot) == "%Y");
+        assert!(d.get_format(&StylePreset::Dot.into()) == "%Y");
         let d = DateTag::Y;
-        assert!(d.get_format(DateStyle::Dot) == "%Y");
+        assert!(d.get_format(&StylePreset::Dot.into()) == "%Y");
     }
 
     #[test]
     fn test_get_format_year_slash() {
         // test year-related variants
         let d = DateTag::Yearly;
-        assert!(d.get_format(DateStyle::Slash) == "%Y");
+        assert!(d.get_format(&StylePreset::Slash.into()) == "%Y");
         let d = DateTag::Y;
-        assert!(d.get_format(DateStyle::Slash) == "%Y");
+        assert!(d.get_format(&StylePreset::Slash.into()) == "%Y");
     }
 
     #[test]
     fn test_get_format_year_colon() {
         // test year-related variants
         let d = DateTag::Yearly;
-        assert!(d.get_format(DateStyle::Colon) == "%Y");
+        assert!(d.get_format(&StylePreset::Colon.into()) == "%Y");
         let d = DateTag::Y;
-        assert!(d.get_format(DateStyle::Colon) == "%Y");
+        assert!(d.get_format(&StylePreset::Colon.into()) == "%Y");
     }
 
     #[test]
     fn test_get_format_year_dash() {
         // test year-related variants
         let d = DateTag::Yearly;
-        assert!(d.get_format(DateStyle::Dash) == "%Y");
+        assert!(d.get_format(&StylePreset::Dash.into()) == "%Y");
         let d = DateTag::Y;
-        assert!(d.get_format(DateStyle::Dash) == "%Y");
+        assert!(d.get_format(&StylePreset::Dash.into()) == "%Y");
     }
 
     #[test]
     fn test_get_format_month_plain() {
         // test month-related variants
         let d = DateTag::Monthly;
-        assert!(d.get_format(DateStyle::Plain) == "%Y%m");
+        assert!(d.get_format(&StylePreset::Plain.into()) == "%Y%m");
         let d = DateTag::M;
-        assert!(d.get_format(DateStyle::Plain) == "%Y%m");
+        assert!(d.get_format(&StylePreset::Plain.into()) == "%Y%m");
     }
 
     #[test]
     fn test_get_format_month_dot() {
         // test month-related variants
         let d = DateTag::Monthly;
-        assert!(d.get_format(DateStyle::Dot) == "%Y.%m");
+        assert!(d.get_format(&StylePreset::Dot.into()) == "%Y.%m");
         let d = DateTag::M;
-        assert!(d.get_format(DateStyle::Dot) == "%Y.%m");
+        assert!(d.get_format(&StylePreset::Dot.into()) == "%Y.%m");
     }
 
     #[test]
     fn test_get_format_month_slash() {
         // test month-related variants
         let d = DateTag::Monthly;
-        assert!(d.get_format(DateStyle::Slash) == "%Y/%m");
+        assert!(d.get_format(&StylePreset::Slash.into()) == "%Y/%m");
         let d = DateTag::M;
-        assert!(d.get_format(DateStyle::Slash) == "%Y/%m");
+        assert!(d.get_format(&StylePreset::Slash.into()) == "%Y/%m");
     }
 
     #[test]
     fn test_get_format_month_colon() {
         // test month-related variants
         let d = DateTag::Monthly;
-        assert!(d.get_format(DateStyle::Colon) == "%Y:%m");
+        assert!(d.get_format(&StylePreset::Colon.into()) == "%Y:%m");
         let d = DateTag::M;
-        assert!(d.get_format(DateStyle::Colon) == "%Y:%m");
+        assert!(d.get_format(&StylePreset::Colon.into()) == "%Y:%m");
     }
 
     #[test]
     fn test_get_format_month_dash() {
         // test month-related variants
         let d = DateTag::Monthly;
-        assert!(d.get_format(DateStyle::Dash) == "%Y-%m");
+        assert!(d.get_format(&StylePreset::Dash.into()) == "%Y-%m");
         let d = DateTag::M;
-        assert!(d.get_format(DateStyle::Dash) == "%Y-%m");
+        assert!(d.get_format(&StylePreset::Dash.into()) == "%Y-%m");
     }
 
     #[test]
     fn test_get_format_week_plain() {
         // test month-related variants
         let d = DateTag::Weekly;
-        assert!(d.get_format(DateStyle::Plain) == "%G%V");
+        assert!(d.get_format(&StylePreset::Plain.into()) == "%G%V");
         let d = DateTag::W;
-        assert!(d.get_format(DateStyle::Plain) == "%G%V");
+        assert!(d.get_format(&StylePreset::Plain.into()) == "%G%V");
     }
 
     #[test]
     fn test_get_format_week_dot() {
         // test month-related variants
         let d = DateTag::Weekly;
-        assert!(d.get_format(DateStyle::Dot) == "%G.%V");
+        assert!(d.get_format(&StylePreset::Dot.into()) == "%G.%V");
         let d = DateTag::W;
-        assert!(d.get_format(DateStyle::Dot) == "%G.%V");
+        assert!(d.get_format(&StylePreset::Dot.into()) == "%G.%V");
     }
 
     #[test]
     fn test_get_format_week_slash() {
         // test month-related variants
         let d = DateTag::Weekly;
-        assert!(d.get_format(DateStyle::Slash) == "%G/%V");
+        assert!(d.get_format(&StylePreset::Slash.into()) == "%G/%V");
         let d = DateTag::W;
-        assert!(d.get_format(DateStyle::Slash) == "%G/%V");
+        assert!(d.get_format(&StylePreset::Slash.into()) == "%G/%V");
     }
 
     #[test]
     fn test_get_format_week_colon() {
         // test month-related variants
         let d = DateTag::Weekly;
-        assert!(d.get_format(DateStyle::Colon) == "%G:%V");
+        assert!(d.get_format(&StylePreset::Colon.into()) == "%G:%V");
         let d = DateTag::W;
-        assert!(d.get_format(DateStyle::Colon) == "%G:%V");
+        assert!(d.get_format(&StylePreset::Colon.into()) == "%G:%V");
     }
 
     #[test]
     fn test_get_format_week_dash() {
         // test month-related variants
         let d = DateTag::Weekly;
-        assert!(d.get_format(DateStyle::Dash) == "%G-%V");
+        assert!(d.get_format(&StylePreset::Dash.into()) == "%G-%V");
         let d = DateTag::W;
-        assert!(d.get_format(DateStyle::Dash) == "%G-%V");
+        assert!(d.get_format(&StylePreset::Dash.into()) == "%G-%V");
     }
 
     #[test]
     fn test_get_format_day_plain() {
         // test day-related variants
         let d = DateTag::Daily;
-        assert!(d.get_format(DateStyle::Plain) == "%Y%m%d");
+        assert!(d.get_format(&StylePreset::Plain.into()) == "%Y%m%d");
         let d = DateTag::D;
-        assert!(d.get_format(DateStyle::Plain) == "%Y%m%d");
+        assert!(d.get_format(&StylePreset::Plain.into()) == "%Y%m%d");
     }
 
     #[test]
     fn test_get_format_day_dot() {
         // test day-related variants
         let d = DateTag::Daily;
-        assert!(d.get_format(DateStyle::Dot) == "%Y.%m.%d");
+        assert!(d.get_format(&StylePreset::Dot.into()) == "%Y.%m.%d");
         let d = DateTag::D;
-        assert!(d.get_format(DateStyle::Dot) == "%Y.%m.%d");
+        assert!(d.get_format(&StylePreset::Dot.into()) == "%Y.%m.%d");
     }
 
     #[test]
     fn test_get_format_day_slash() {
         // test day-related variants
         let d = DateTag::Daily;
-        assert!(d.get_format(DateStyle::Slash) == "%Y/%m/%d");
+        assert!(d.get_format(&StylePreset::Slash.into()) == "%Y/%m/%d");
         let d = DateTag::D;
-        assert!(d.get_format(DateStyle::Slash) == "%Y/%m/%d");
+        assert!(d.get_format(&StylePreset::Slash.into()) == "%Y/%m/%d");
     }
 
     #[test]
     fn test_get_format_day_colon() {
         // test day-related variants
         let d = DateTag::Daily;
-        assert!(d.get_format(DateStyle::Colon) == "%Y:%m:%d");
+        assert!(d.get_format(&StylePreset::Colon.into()) == "%Y:%m:%d");
         let d = DateTag::D;
-        assert!(d.get_format(DateStyle::Colon) == "%Y:%m:%d");
+        assert!(d.get_format(&StylePreset::Colon.into()) == "%Y:%m:%d");
     }
 
     #[test]
     fn test_get_format_day_dash() {
         // test day-related variants
         let d = DateTag::Daily;
-        assert!(d.get_format(DateStyle::Dash) == "%Y-%m-%d");
+        assert!(d.get_format(&StylePreset::Dash.into()) == "%Y-%m-%d");
         let d = DateTag::D;
-        assert!(d.get_format(DateStyle::Dash) == "%Y-%m-%d");
+        assert!(d.get_format(&StylePreset::Dash.into()) == "%Y-%m-%d");
+    }
+
+    #[test]
+    fn test_get_format_european() {
+        let s = StylePreset::European.into();
+        assert_eq!(DateTag::Daily.get_format(&s), "%d.%m.%Y");
+        assert_eq!(DateTag::Monthly.get_format(&s), "%m.%Y");
+        assert_eq!(DateTag::Weekly.get_format(&s), "%V.%G");
+        assert_eq!(DateTag::Yearly.get_format(&s), "%Y");
+    }
+
+    #[test]
+    fn test_get_format_us() {
+        let s = StylePreset::Us.into();
+        assert_eq!(DateTag::D.get_format(&s), "%m/%d/%Y");
+        assert_eq!(DateTag::M.get_format(&s), "%m/%Y");
+    }
+
+    #[test]
+    fn test_get_format_custom() {
+        let s = DateStyle {
+            separator: String::from("_"),
+            order: FieldOrder::Ymd,
+            short_year: false,
+            month: MonthRepr::Number,
+            hive: None,
+            week_designator: false,
+            fiscal: false,
+        };
+        assert_eq!(DateTag::D.get_format(&s), "%Y_%m_%d");
+        assert_eq!(DateTag::W.get_format(&s), "%G_%V");
+    }
+
+    #[test]
+    fn test_get_format_compact() {
+        let s = StylePreset::Compact.into();
+        assert_eq!(DateTag::D.get_format(&s), "%y%m%d");
+        assert_eq!(DateTag::W.get_format(&s), "%g%V");
+        assert_eq!(DateTag::M.get_format(&s), "%y%m");
+        assert_eq!(DateTag::Y.get_format(&s), "%y");
+    }
+
+    #[test]
+    fn test_get_format_quarter() {
+        assert_eq!(DateTag::Q.get_format(&StylePreset::Plain.into()), "%YQ%q");
+        assert_eq!(
+            DateTag::Quarterly.get_format(&StylePreset::Dash.into()),
+            "%Y-Q%q"
+        );
+        assert_eq!(
+            DateTag::Q.get_format(&StylePreset::Hive.into()),
+            "year=%Y/quarter=%q"
+        );
+    }
+
+    #[test]
+    fn test_get_format_hive() {
+        let s = StylePreset::Hive.into();
+        assert_eq!(DateTag::D.get_format(&s), "year=%Y/month=%m/day=%d");
+        assert_eq!(DateTag::W.get_format(&s), "year=%G/week=%V");
+        assert_eq!(DateTag::Y.get_format(&s), "year=%Y");
+    }
+
+    #[test]
+    fn test_get_format_iso_week() {
+        let s = StylePreset::IsoWeek.into();
+        assert_eq!(DateTag::W.get_format(&s), "%G-W%V");
+        assert_eq!(DateTag::WeekDay.get_format(&s), "%G-W%V-%u");
+        assert_eq!(DateTag::D.get_format(&s), "%Y-%m-%d");
+        let s = StylePreset::IsoWeekCompact.into();
+        assert_eq!(DateTag::Weekly.get_format(&s), "%GW%V");
+        assert_eq!(DateTag::Wd.get_format(&s), "%GW%V%u");
+        assert_eq!(DateTag::Wd.get_format(&StylePreset::Plain.into()), "%G%V%u");
+    }
+
+    #[test]
+    fn test_get_format_ordinal() {
+        assert_eq!(DateTag::O.get_format(&StylePreset::Plain.into()), "%Y%j");
+        assert_eq!(
+            DateTag::Ordinal.get_format(&StylePreset::Dash.into()),
+            "%Y-%j"
+        );
+        assert_eq!(
+            DateTag::O.get_format(&StylePreset::European.into()),
+            "%j.%Y"
+        );
+    }
+
+    #[test]
+    fn test_get_format_week_of_month() {
+        assert_eq!(
+            DateTag::Mw.get_format(&StylePreset::Plain.into()),
+            "%Y%mW%K"
+        );
+        assert_eq!(
+            DateTag::WeekOfMonth.get_format(&StylePreset::Dash.into()),
+            "%Y-%m-W%K"
+        );
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(DateTag::Y.name(), "yearly");
+        assert_eq!(DateTag::Q.name(), "quarterly");
+        assert_eq!(DateTag::Weekly.name(), "weekly");
+        assert_eq!(DateTag::Wd.name(), "week-day");
+        assert_eq!(DateTag::M.name(), "monthly");
+        assert_eq!(DateTag::Daily.name(), "daily");
+        assert_eq!(DateTag::O.name(), "ordinal");
+        assert_eq!(DateTag::Mw.name(), "week-of-month");
+        assert_eq!(DateTag::Ed.name(), "epoch-day");
+        assert_eq!(DateTag::EpochSeconds.name(), "epoch-seconds");
+        assert_eq!(DateTag::B36.name(), "base36");
+        assert_eq!(DateTag::X.name(), "hex");
+    }
+
+    #[test]
+    fn test_period_year() {
+        let p = DateTag::Yearly.period(&ymd(2024, 4, 27), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 1, 1), ymd(2024, 12, 31))));
+    }
+
+    #[test]
+    fn test_period_quarter() {
+        let p = DateTag::Quarterly.period(&ymd(2024, 4, 27), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 4, 1), ymd(2024, 6, 30))));
+        let p = DateTag::Q.period(&ymd(2024, 12, 31), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 10, 1), ymd(2024, 12, 31))));
+    }
+
+    #[test]
+    fn test_period_week() {
+        // ISO week 2025-W01 starts in 2024
+        let p = DateTag::Weekly.period(&ymd(2025, 1, 1), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 12, 30), ymd(2025, 1, 5))));
+    }
+
+    #[test]
+    fn test_period_week_us() {
+        // US weeks are split at year boundaries
+        let calendar = Calendar {
+            weeks: WeekSystem::Us,
+            ..Calendar::default()
+        };
+        let p = DateTag::Weekly.period(&ymd(2025, 1, 1), &calendar);
+        assert_eq!(p, Some((ymd(2025, 1, 1), ymd(2025, 1, 4))));
+        let calendar = Calendar {
+            weeks: WeekSystem::Sunday,
+            ..Calendar::default()
+        };
+        let p = DateTag::W.period(&ymd(2024, 12, 30), &calendar);
+        assert_eq!(p, Some((ymd(2024, 12, 29), ymd(2024, 12, 31))));
+    }
+
+    #[test]
+    fn test_period_month() {
+        let p = DateTag::Monthly.period(&ymd(2024, 2, 10), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 2, 1), ymd(2024, 2, 29))));
+        let p = DateTag::M.period(&ymd(2024, 12, 31), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 12, 1), ymd(2024, 12, 31))));
+        let p = DateTag::M.period(&NaiveDate::MAX, &Calendar::default());
+        assert_eq!(p, None);
+        let p = DateTag::Q.period(&NaiveDate::MAX, &Calendar::default());
+        assert_eq!(p, None);
+    }
+
+    #[test]
+    fn test_period_week_of_month() {
+        let p = DateTag::WeekOfMonth.period(&ymd(2024, 4, 27), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 4, 22), ymd(2024, 4, 28))));
+        let p = DateTag::Mw.period(&ymd(2024, 2, 29), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 2, 29), ymd(2024, 2, 29))));
+    }
+
+    #[test]
+    fn test_period_day() {
+        let p = DateTag::D.period(&ymd(2024, 4, 27), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 4, 27), ymd(2024, 4, 27))));
+        let p = DateTag::WeekDay.period(&ymd(2024, 4, 27), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 4, 27), ymd(2024, 4, 27))));
+        let p = DateTag::Ordinal.period(&ymd(2024, 4, 27), &Calendar::default());
+        assert_eq!(p, Some((ymd(2024, 4, 27), ymd(2024, 4, 27))));
     }
 }
@@ -0,0 +1,148 @@
+use chrono::{NaiveDate, TimeDelta};
+
+/// seconds of a day
+const DAY_SECONDS: i64 = 86400;
+
+/// digits of base 36 numbers
+const BASE36_DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// default epoch of encoded tags
+pub fn unix_epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
+}
+
+/// encodings of date tags counting days or seconds since an epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// decimal days (e.g. 19840)
+    Days,
+    /// decimal seconds, at midnight UTC (e.g. 1714176000)
+    Seconds,
+    /// base 36 days (e.g. fb4)
+    Base36,
+    /// hexadecimal days (e.g. 4d80)
+    Hex,
+}
+
+impl Encoding {
+    /// datetag specifier rendering the encoding
+    pub fn specifier(&self) -> &'static str {
+        match self {
+            Encoding::Days => "%E",
+            Encoding::Seconds => "%i",
+            Encoding::Base36 => "%N",
+            Encoding::Hex => "%L",
+        }
+    }
+
+    /// encoding rendered by a specifier, without modifiers
+    pub fn from_specifier(spec: char) -> Option<Self> {
+        match spec {
+            'E' => Some(Encoding::Days),
+            'i' => Some(Encoding::Seconds),
+            'N' => Some(Encoding::Base36),
+            'L' => Some(Encoding::Hex),
+            _ => None,
+        }
+    }
+
+    /// encode a date, relative to the given epoch
+    pub fn encode(&self, date: &NaiveDate, epoch: &NaiveDate) -> String {
+        let days = (*date - *epoch).num_days();
+
+        match self {
+            Encoding::Days => days.to_string(),
+            Encoding::Seconds => (days * DAY_SECONDS).to_string(),
+            Encoding::Base36 => to_radix(days, 36),
+            Encoding::Hex => to_radix(days, 16),
+        }
+    }
+
+    /// decode a date, relative to the given epoch
+    ///
+    /// Seconds are truncated to the day containing them.
+    pub fn decode(&self, s: &str, epoch: &NaiveDate) -> Option<NaiveDate> {
+        let s = s.trim();
+
+        let days = match self {
+            Encoding::Days => s.parse::<i64>().ok()?,
+            Encoding::Seconds => s.parse::<i64>().ok()?.div_euclid(DAY_SECONDS),
+            Encoding::Base36 => i64::from_str_radix(&s.to_lowercase(), 36).ok()?,
+            Encoding::Hex => i64::from_str_radix(s, 16).ok()?,
+        };
+
+        epoch.checked_add_signed(TimeDelta::try_days(days)?)
+    }
+}
+
+/// lowercase representation of a number in the given base (2-36)
+fn to_radix(value: i64, base: u32) -> String {
+    let mut n = value.unsigned_abs();
+    let mut digits = Vec::new();
+
+    loop {
+        digits.push(BASE36_DIGITS[(n % base as u64) as usize]);
+        n /= base as u64;
+        if n == 0 {
+            break;
+        }
+    }
+    if value < 0 {
+        digits.push(b'-');
+    }
+    digits.reverse();
+
+    String::from_utf8(digits).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_encode() {
+        let date = ymd(2024, 4, 27);
+        let epoch = unix_epoch();
+        assert_eq!(Encoding::Days.encode(&date, &epoch), "19840");
+        assert_eq!(Encoding::Seconds.encode(&date, &epoch), "1714176000");
+        assert_eq!(Encoding::Base36.encode(&date, &epoch), "fb4");
+        assert_eq!(Encoding::Hex.encode(&date, &epoch), "4d80");
+        assert_eq!(Encoding::Days.encode(&epoch, &date), "-19840");
+        assert_eq!(Encoding::Base36.encode(&epoch, &date), "-fb4");
+        assert_eq!(Encoding::Base36.encode(&date, &ymd(2024, 1, 1)), "39");
+    }
+
+    #[test]
+    fn test_decode() {
+        let date = ymd(2024, 4, 27);
+        let epoch = unix_epoch();
+        assert_eq!(Encoding::Days.decode("19840", &epoch), Some(date));
+        assert_eq!(Encoding::Seconds.decode("1714176000", &epoch), Some(date));
+        assert_eq!(Encoding::Seconds.decode("1714262399", &epoch), Some(date));
+        assert_eq!(Encoding::Base36.decode("FB4", &epoch), Some(date));
+        assert_eq!(Encoding::Hex.decode("4d80", &epoch), Some(date));
+        assert_eq!(Encoding::Days.decode("-19840", &date), Some(epoch));
+        assert_eq!(Encoding::Hex.decode("4g80", &epoch), None);
+        assert_eq!(Encoding::Days.decode("", &epoch), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let epoch = ymd(2000, 1, 1);
+        for encoding in [
+            Encoding::Days,
+            Encoding::Seconds,
+            Encoding::Base36,
+            Encoding::Hex,
+        ] {
+            for date in [ymd(1999, 12, 31), epoch, ymd(2024, 4, 27)] {
+                let s = encoding.encode(&date, &epoch);
+                assert_eq!(encoding.decode(&s, &epoch), Some(date));
+            }
+        }
+    }
+}
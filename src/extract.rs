@@ -0,0 +1,290 @@
+//! Extraction of the date tags embedded in arbitrary text (e.g. release
+//! notes or changelogs), in the spirit of grep
+//!
+//! Digits are everywhere in text, so matches are filtered according to a
+//! strictness level: tags chained with other numbers (e.g. phone numbers
+//! such as 800-2024-12) are ignored by default, and strict extraction
+//! only keeps tags holding separators or designators (e.g. 2024-04-27 or
+//! 2024Q2, but neither 20240427 nor 2024).
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, NaiveDate};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    calendar::Calendar,
+    finder::{Finder, Found, DEFAULT_YEARS},
+};
+
+/// arguments of the extract subcommand
+#[derive(Debug, clap::Args)]
+pub struct ExtractArgs {
+    /// Files to scan, reading the standard input if not specified
+    files: Vec<PathBuf>,
+
+    /// Filtering of the matches, to avoid false positives
+    #[arg(value_enum, long, default_value_t = Strictness::Normal)]
+    strictness: Strictness,
+
+    /// Ignore tags before the given year
+    #[arg(long, value_name = "YEAR", default_value_t = *DEFAULT_YEARS.start())]
+    min_year: i32,
+
+    /// Ignore tags after the given year
+    #[arg(long, value_name = "YEAR", default_value_t = *DEFAULT_YEARS.end())]
+    max_year: i32,
+
+    /// Write matches as JSON lines
+    #[arg(long)]
+    json: bool,
+}
+
+/// filtering levels of the extracted tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Strictness {
+    /// keep every tag found
+    Loose,
+    /// ignore tags chained with other numbers (e.g. 1.2024.04 or 800-2024-12)
+    Normal,
+    /// also ignore tags made of digits only (e.g. 20240427 or 2024)
+    Strict,
+}
+
+/// tag extracted from a line
+#[derive(Debug, Serialize)]
+struct Match<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>,
+    line: usize,
+    column: usize,
+    tag: &'a str,
+    date: NaiveDate,
+    tag_type: &'static str,
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+}
+
+/// separators chaining numbers together (e.g. in 1.2024.04)
+const CHAINS: &[char] = &['-', '.', '/', ':'];
+
+/// true if the found tag is kept at the given strictness level
+fn accepted(line: &str, found: &Found, strictness: Strictness) -> bool {
+    let chained = |separator: Option<char>, next: Option<char>| {
+        separator.is_some_and(|c| CHAINS.contains(&c)) && next.is_some_and(|c| c.is_ascii_digit())
+    };
+    let mut before = line[..found.start].chars().rev();
+    let mut after = line[found.end..].chars();
+
+    match strictness {
+        Strictness::Loose => true,
+        Strictness::Normal => {
+            !chained(before.next(), before.next()) && !chained(after.next(), after.next())
+        }
+        Strictness::Strict => {
+            accepted(line, found, Strictness::Normal)
+                && found.text.chars().any(|c| !c.is_ascii_digit())
+        }
+    }
+}
+
+/// write the tags of the lines of an input, returning the number of matches
+fn extract(
+    args: &ExtractArgs,
+    finder: &Finder,
+    calendar: &Calendar,
+    path: Option<&str>,
+    mut input: impl BufRead,
+    output: &mut impl Write,
+) -> Result<usize> {
+    let years = args.min_year..=args.max_year;
+    let mut count = 0;
+
+    let mut buffer = Vec::new();
+    let mut number = 0;
+    while input.read_until(b'\n', &mut buffer)? > 0 {
+        number += 1;
+        let line = String::from_utf8_lossy(&buffer);
+
+        for found in finder.find_all(&line) {
+            if !years.contains(&found.date.year()) || !accepted(&line, &found, args.strictness) {
+                continue;
+            }
+
+            let Some((period_start, period_end)) = found.tag_type.period(&found.date, calendar)
+            else {
+                continue;
+            };
+            let m = Match {
+                path,
+                line: number,
+                column: line[..found.start].chars().count() + 1,
+                tag: &found.text,
+                date: found.date,
+                tag_type: found.tag_type.name(),
+                period_start,
+                period_end,
+            };
+            if args.json {
+                writeln!(output, "{}", serde_json::to_string(&m)?)?;
+            } else {
+                let position = match m.path {
+                    Some(path) => format!("{}:{}:{}", path, m.line, m.column),
+                    None => format!("{}:{}", m.line, m.column),
+                };
+                writeln!(
+                    output,
+                    "{}\t{}\t{}\t{}\t{}..{}",
+                    position, m.tag, m.date, m.tag_type, m.period_start, m.period_end
+                )?;
+            }
+            count += 1;
+        }
+        buffer.clear();
+    }
+
+    Ok(count)
+}
+
+/// write the tags found in files or in the standard input, failing if
+/// none is found
+pub fn run(
+    args: &ExtractArgs,
+    finder: &Finder,
+    calendar: &Calendar,
+    mut output: impl Write,
+) -> Result<()> {
+    let mut count = 0;
+
+    if args.files.is_empty() {
+        count += extract(
+            args,
+            finder,
+            calendar,
+            None,
+            io::stdin().lock(),
+            &mut output,
+        )?;
+    }
+    for path in &args.files {
+        let file = File::open(path).with_context(|| format!("cannot read '{}'", path.display()))?;
+        let name = path.display().to_string();
+        count += extract(
+            args,
+            finder,
+            calendar,
+            Some(&name),
+            BufReader::new(file),
+            &mut output,
+        )?;
+    }
+    output.flush()?;
+
+    if count == 0 {
+        bail!("no date tag found");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::LazyLock;
+
+    use clap::Parser;
+
+    use crate::utils::ParseOptions;
+
+    static FINDER: LazyLock<Finder> = LazyLock::new(|| Finder::new(&ParseOptions::default()));
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: ExtractArgs,
+    }
+
+    fn tags(options: &str, input: &str) -> Vec<String> {
+        let args = Cli::parse_from(format!("extract {}", options).split_whitespace()).args;
+        let mut output = Vec::new();
+        extract(
+            &args,
+            &FINDER,
+            &Calendar::default(),
+            None,
+            input.as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| l.split('\t').nth(1).unwrap_or_default().to_string())
+            .collect()
+    }
+
+    const TEXT: &str = "## 2024.04 (released 27-Apr-2024)\n\
+        Call +1 800-2024-12 or see v1.2024.04, build 20240427 of 2024Q2\n";
+
+    #[test]
+    fn test_strictness() {
+        assert_eq!(
+            tags("--strictness loose", TEXT),
+            [
+                "2024.04",
+                "27-Apr-2024",
+                "2024-12",
+                "2024.04",
+                "20240427",
+                "2024Q2"
+            ]
+        );
+        assert_eq!(
+            tags("", TEXT),
+            ["2024.04", "27-Apr-2024", "20240427", "2024Q2"]
+        );
+        assert_eq!(
+            tags("--strictness strict", TEXT),
+            ["2024.04", "27-Apr-2024", "2024Q2"]
+        );
+    }
+
+    #[test]
+    fn test_years() {
+        assert_eq!(
+            tags(
+                "--min-year 2000 --max-year 2030",
+                "from 1999.12 to 2024-04-27, not 2031\n"
+            ),
+            ["2024-04-27"]
+        );
+    }
+
+    #[test]
+    fn test_position() {
+        let args = Cli::parse_from(["extract"]).args;
+        let mut output = Vec::new();
+        extract(
+            &args,
+            &FINDER,
+            &Calendar::default(),
+            Some("NEWS"),
+            "intro\n« 2024-W17 »\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "NEWS:2:3\t2024-W17\t2024-04-22\tweekly\t2024-04-22..2024-04-28\n"
+        );
+    }
+}
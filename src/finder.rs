@@ -0,0 +1,318 @@
+use std::ops::RangeInclusive;
+
+use chrono::{Datelike, NaiveDate};
+use regex::Regex;
+
+use crate::{
+    calendar::Calendar,
+    datestyle::{DateStyle, StylePreset},
+    datetag::DateTag,
+    locale::DateFormatter,
+    utils::{self, ParseOptions},
+};
+
+/// default range of the years of found tags
+pub const DEFAULT_YEARS: RangeInclusive<i32> = 1900..=2199;
+
+/// styles of the tags looked for, Hive and retail tags excepted
+const STYLES: &[StylePreset] = &[
+    StylePreset::Plain,
+    StylePreset::Dash,
+    StylePreset::Dot,
+    StylePreset::Underscore,
+    StylePreset::Slash,
+    StylePreset::Colon,
+    StylePreset::Space,
+    StylePreset::European,
+    StylePreset::Us,
+    StylePreset::Compact,
+    StylePreset::MonthAbbr,
+    StylePreset::MonthName,
+    StylePreset::IsoWeek,
+    StylePreset::IsoWeekCompact,
+];
+
+/// tag types looked for, from the most to the least specific
+const TAG_TYPES: &[DateTag] = &[
+    DateTag::Daily,
+    DateTag::WeekDay,
+    DateTag::Ordinal,
+    DateTag::WeekOfMonth,
+    DateTag::Monthly,
+    DateTag::Quarterly,
+    DateTag::Weekly,
+    DateTag::Yearly,
+];
+
+/// date tag found in a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found {
+    /// byte offset of the first character of the tag
+    pub start: usize,
+    /// byte offset following the last character of the tag
+    pub end: usize,
+    /// tag, as found (e.g. '2024-04-27')
+    pub text: String,
+    /// date of the tag, the first day of its period for non-daily tags
+    pub date: NaiveDate,
+    /// type of the tag
+    pub tag_type: DateTag,
+    /// style of the tag
+    pub style: StylePreset,
+}
+
+/// date tag format looked for
+struct Pattern {
+    regex: Regex,
+    format: String,
+    options: ParseOptions,
+    tag_type: DateTag,
+    style: StylePreset,
+}
+
+/// finder of date tags embedded in strings (e.g. 'backup_2024-04-27.tar')
+///
+/// Tags are looked for in any style and tag type datetag produces, apart
+/// from Hive, retail and encoded tags. Week tags require their 'W'
+/// designator (e.g. 2024W17), plain ones being ambiguous with monthly or
+/// ordinal tags, and two-digit years are only looked for in daily tags.
+pub struct Finder {
+    patterns: Vec<Pattern>,
+    formatter: DateFormatter,
+    /// years of the tags to find, others being ignored
+    pub years: RangeInclusive<i32>,
+}
+
+impl Finder {
+    /// look for tags read according to the given locale, week numbering
+    /// system and century pivot
+    pub fn new(options: &ParseOptions) -> Self {
+        let mut patterns: Vec<Pattern> = Vec::new();
+
+        for tag_type in TAG_TYPES {
+            for preset in STYLES {
+                let style = DateStyle::from(*preset);
+                if style.week_designator != matches!(tag_type, DateTag::Weekly | DateTag::WeekDay)
+                    || (style.short_year && *tag_type != DateTag::Daily)
+                {
+                    continue;
+                }
+
+                let format = tag_type.get_format(&style);
+                if patterns.iter().any(|p| p.format == format) {
+                    continue;
+                }
+
+                patterns.push(Pattern {
+                    regex: Regex::new(&format!("(?i){}", regex_of(&format))).unwrap(),
+                    format,
+                    options: ParseOptions {
+                        order: style.order,
+                        short_year: style.short_year,
+                        ..*options
+                    },
+                    tag_type: *tag_type,
+                    style: *preset,
+                });
+            }
+        }
+
+        Finder {
+            patterns,
+            formatter: DateFormatter {
+                locale: options.locale,
+                calendar: Calendar {
+                    weeks: options.weeks,
+                    ..Calendar::default()
+                },
+            },
+            years: DEFAULT_YEARS,
+        }
+    }
+
+    /// first tag of a string, if any
+    pub fn find(&self, s: &str) -> Option<Found> {
+        self.find_all(s).into_iter().next()
+    }
+
+    /// all the tags of a string, from left to right
+    ///
+    /// Overlapping tags are resolved in favor of the leftmost, then the
+    /// longest, then the most specific one.
+    pub fn find_all(&self, s: &str) -> Vec<Found> {
+        let mut candidates: Vec<(usize, Found)> = Vec::new();
+
+        for (rank, pattern) in self.patterns.iter().enumerate() {
+            for m in pattern.regex.find_iter(s) {
+                if let Some(found) = self.check(s, m.start(), m.end(), pattern) {
+                    candidates.push((rank, found));
+                }
+            }
+        }
+        candidates.sort_by_key(|(rank, f)| (f.start, std::cmp::Reverse(f.end), *rank));
+
+        let mut found: Vec<Found> = Vec::new();
+        for (_, candidate) in candidates {
+            if found.last().is_none_or(|f| f.end <= candidate.start) {
+                found.push(candidate);
+            }
+        }
+
+        found
+    }
+
+    /// tag matching a pattern at the given position, if it is bounded and
+    /// formatted back as found
+    fn check(&self, s: &str, start: usize, end: usize, pattern: &Pattern) -> Option<Found> {
+        let text = &s[start..end];
+
+        // tags cannot be part of a longer number or word
+        let before = s[..start].chars().next_back();
+        let after = s[end..].chars().next();
+        let first = text.chars().next()?;
+        let last = text.chars().next_back()?;
+        if !is_boundary(before, first) || !is_boundary(after, last) {
+            return None;
+        }
+
+        let date = utils::checked_date_from_str_with(text, &pattern.options)?;
+        if !self.years.contains(&date.year())
+            || !self
+                .formatter
+                .format(&date, &pattern.format)
+                .eq_ignore_ascii_case(text)
+        {
+            return None;
+        }
+
+        Some(Found {
+            start,
+            end,
+            text: text.to_string(),
+            date,
+            tag_type: pattern.tag_type,
+            style: pattern.style,
+        })
+    }
+}
+
+/// true if a neighbouring character does not extend the edge of a tag
+fn is_boundary(neighbour: Option<char>, edge: char) -> bool {
+    match neighbour {
+        Some(c) if edge.is_ascii_digit() => !c.is_ascii_digit(),
+        Some(c) if edge.is_alphabetic() => !c.is_alphabetic(),
+        _ => true,
+    }
+}
+
+/// regular expression matching the output of a format string
+fn regex_of(format: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            regex.push_str(&regex::escape(&c.to_string()));
+            continue;
+        }
+
+        regex.push_str(match chars.next() {
+            Some('Y' | 'G') => r"\d{4}",
+            Some('y' | 'g' | 'm' | 'd' | 'V') => r"\d{2}",
+            Some('j') => r"\d{3}",
+            Some('q') => "[1-4]",
+            Some('K') => "[1-5]",
+            Some('u') => "[1-7]",
+            Some('b' | 'B' | 'h') => r"\p{L}+",
+            Some('%') => "%",
+            _ => "",
+        });
+    }
+
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::LazyLock;
+
+    static FINDER: LazyLock<Finder> = LazyLock::new(|| Finder::new(&ParseOptions::default()));
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn find(s: &str) -> Option<(String, NaiveDate, DateTag)> {
+        FINDER.find(s).map(|f| (f.text, f.date, f.tag_type))
+    }
+
+    #[test]
+    fn test_regex_of() {
+        assert_eq!(regex_of("%Y-%m-%d"), r"\d{4}\-\d{2}\-\d{2}");
+        assert_eq!(regex_of("%YQ%q"), r"\d{4}Q[1-4]");
+    }
+
+    #[test]
+    fn test_find_styles() {
+        let d = ymd(2024, 4, 27);
+        for s in [
+            "backup_20240427.tar",
+            "backup_2024-04-27.tar",
+            "2024.04.27_x",
+            "27.04.2024",
+            "report 04/27/2024",
+            "db_240427.sql",
+            "27-Apr-2024.log",
+        ] {
+            let (_, date, tag_type) = find(s).unwrap();
+            assert_eq!((date, tag_type), (d, DateTag::Daily), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_find_tag_types() {
+        assert_eq!(
+            find("LAB_202404_rel"),
+            Some(("202404".to_string(), ymd(2024, 4, 1), DateTag::Monthly))
+        );
+        assert_eq!(
+            find("sales-2024Q2.csv"),
+            Some(("2024Q2".to_string(), ymd(2024, 4, 1), DateTag::Quarterly))
+        );
+        assert_eq!(
+            find("sprint_2024-W17"),
+            Some(("2024-W17".to_string(), ymd(2024, 4, 22), DateTag::Weekly))
+        );
+        assert_eq!(
+            find("2024-118"),
+            Some(("2024-118".to_string(), ymd(2024, 4, 27), DateTag::Ordinal))
+        );
+        assert_eq!(
+            find("archive_2024"),
+            Some(("2024".to_string(), ymd(2024, 1, 1), DateTag::Yearly))
+        );
+    }
+
+    #[test]
+    fn test_find_nothing() {
+        assert_eq!(find("build_42"), None);
+        assert_eq!(find("20241345"), None);
+        assert_eq!(find("120240427"), None);
+        assert_eq!(find("v3000"), None);
+    }
+
+    #[test]
+    fn test_find_all() {
+        let found: Vec<_> = FINDER
+            .find_all("from 2024-04-27 to 2024-05")
+            .into_iter()
+            .map(|f| (f.start, f.text))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(5, "2024-04-27".to_string()), (19, "2024-05".to_string())]
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use std::borrow::Cow;
+
+use anyhow::{bail, Result};
+use chrono::{
+    format::{Fixed, Item, Numeric, StrftimeItems},
+    NaiveDate,
+};
+
+use crate::locale::DateFormatter;
+
+/// date specifiers supported in custom format strings, with their description
+pub const SPECIFIERS: &[(&str, &str)] = &[
+    ("%Y", "year, zero-padded to 4 digits"),
+    ("%C", "year divided by 100, zero-padded to 2 digits"),
+    ("%y", "year modulo 100, zero-padded to 2 digits"),
+    ("%q", "quarter of year (1-4)"),
+    ("%m", "month number (01-12)"),
+    ("%b", "abbreviated month name"),
+    ("%B", "full month name"),
+    ("%h", "same as %b"),
+    ("%d", "day of month (01-31)"),
+    ("%e", "day of month, space-padded"),
+    ("%a", "abbreviated weekday name"),
+    ("%A", "full weekday name"),
+    ("%w", "day of the week, from Sunday (0-6)"),
+    ("%u", "day of the week, from Monday (1-7)"),
+    ("%U", "week number, starting with the first Sunday (00-53)"),
+    ("%W", "week number, starting with the first Monday (00-53)"),
+    ("%G", "ISO 8601 week-based year"),
+    ("%g", "ISO 8601 week-based year modulo 100"),
+    ("%V", "ISO 8601 week number (01-53)"),
+    ("%j", "day of the year (001-366)"),
+    ("%K", "week of the month, days 1-7 being week 1 (1-5)"),
+    ("%J", "astronomical Julian day number"),
+    ("%E", "days since the epoch (see '--epoch')"),
+    ("%i", "seconds since the epoch, at midnight UTC"),
+    ("%N", "base 36 days since the epoch"),
+    ("%L", "hexadecimal days since the epoch"),
+    ("%D", "month-day-year format, same as %m/%d/%y"),
+    ("%x", "locale's date representation"),
+    ("%F", "year-month-day format, same as %Y-%m-%d"),
+    ("%v", "day-month-year format, same as %e-%b-%Y"),
+    ("%%", "literal percent sign"),
+];
+
+/// specifiers rendered by datetag itself, not by chrono
+const EXTENSIONS: &[&str] = &["%K", "%J", "%E", "%i", "%N", "%L"];
+
+/// maximum length of a single specifier (e.g. '%:::z')
+const MAX_SPECIFIER_LEN: usize = 5;
+
+/// check that a custom format string only contains date specifiers
+///
+/// Reported positions start from 1 and count characters.
+pub fn validate(format: &str) -> Result<()> {
+    let chars: Vec<(usize, char)> = format.char_indices().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].1 != '%' {
+            i += 1;
+            continue;
+        }
+
+        let start = chars[i].0;
+        if let Some(ext) = EXTENSIONS.iter().find(|e| format[start..].starts_with(*e)) {
+            i += ext.chars().count();
+            continue;
+        }
+
+        // look for the shortest valid specifier starting at the current position
+        let spec = (i + 2..=(i + MAX_SPECIFIER_LEN).min(chars.len()))
+            .map(|j| {
+                (
+                    j,
+                    &format[start..chars.get(j).map_or(format.len(), |c| c.0)],
+                )
+            })
+            .find(|(_, s)| StrftimeItems::new(s).all(|item| item != Item::Error));
+
+        let Some((end, spec)) = spec else {
+            // report the specifier along with its padding modifier, if any
+            let end = chars[i + 1..]
+                .iter()
+                .position(|(_, c)| !matches!(c, '-' | '_' | '0'))
+                .map_or(chars.len(), |p| i + p + 2)
+                .min(chars.len());
+            let spec = &format[start..chars.get(end).map_or(format.len(), |c| c.0)];
+
+            if spec == "%" {
+                bail!("incomplete specifier '%' at position {}", i + 1);
+            }
+            bail!("unsupported specifier '{}' at position {}", spec, i + 1);
+        };
+
+        if !StrftimeItems::new(spec).all(|item| is_date_item(&item)) {
+            bail!(
+                "specifier '{}' at position {} requires time or time zone data",
+                spec,
+                i + 1
+            );
+        }
+
+        i = end;
+    }
+
+    Ok(())
+}
+
+/// replace specifiers of a format string with the values returned by the
+/// given function, given the padding modifier (if any) and the specifier
+/// character, other specifiers are kept as is
+pub fn substitute<'a, F>(format: &'a str, mut value: F) -> Cow<'a, str>
+where
+    F: FnMut(Option<char>, char) -> Option<String>,
+{
+    if !format.contains('%') {
+        return Cow::Borrowed(format);
+    }
+
+    let mut s = String::with_capacity(format.len());
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            s.push(c);
+            continue;
+        }
+
+        let pad = chars.next_if(|c| matches!(c, '-' | '_' | '0'));
+        match chars.next() {
+            Some(spec) => match value(pad, spec) {
+                Some(v) => s.push_str(&v),
+                None => {
+                    s.push('%');
+                    s.extend(pad);
+                    s.push(spec);
+                }
+            },
+            None => {
+                s.push('%');
+                s.extend(pad);
+            }
+        }
+    }
+
+    Cow::Owned(s)
+}
+
+/// true if the item can be rendered using only a date
+fn is_date_item(item: &Item) -> bool {
+    match item {
+        Item::Literal(_) | Item::OwnedLiteral(_) | Item::Space(_) | Item::OwnedSpace(_) => true,
+        Item::Numeric(n, _) => matches!(
+            n,
+            Numeric::Year
+                | Numeric::YearDiv100
+                | Numeric::YearMod100
+                | Numeric::IsoYear
+                | Numeric::IsoYearDiv100
+                | Numeric::IsoYearMod100
+                | Numeric::Quarter
+                | Numeric::Month
+                | Numeric::Day
+                | Numeric::WeekFromSun
+                | Numeric::WeekFromMon
+                | Numeric::IsoWeek
+                | Numeric::NumDaysFromSun
+                | Numeric::WeekdayFromMon
+                | Numeric::Ordinal
+        ),
+        Item::Fixed(f) => matches!(
+            f,
+            Fixed::ShortMonthName
+                | Fixed::LongMonthName
+                | Fixed::ShortWeekdayName
+                | Fixed::LongWeekdayName
+        ),
+        _ => false,
+    }
+}
+
+/// list supported specifiers, along with examples related to the given date
+pub fn list(date: &NaiveDate, formatter: &DateFormatter) -> String {
+    SPECIFIERS
+        .iter()
+        .map(|(spec, description)| {
+            format!(
+                "{:<4} {:<12} {}\n",
+                spec,
+                formatter.format(date, spec),
+                description
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calendar::Calendar, weeks::WeekSystem};
+    use chrono::Locale;
+
+    #[test]
+    fn test_validate_valid() {
+        assert!(validate("").is_ok());
+        assert!(validate("plain text").is_ok());
+        assert!(validate("%Y%m%d").is_ok());
+        assert!(validate("%Y-W%V-%u").is_ok());
+        assert!(validate("%-d/%_m/%y %%").is_ok());
+        assert!(validate("%F %D %v %x").is_ok());
+        assert!(validate("%A, %e %B (%q)").is_ok());
+        assert!(validate("àè %Y").is_ok());
+    }
+
+    #[test]
+    fn test_validate_unsupported() {
+        let e = validate("%Y%Q").unwrap_err();
+        assert_eq!(e.to_string(), "unsupported specifier '%Q' at position 3");
+        let e = validate("é%-Q").unwrap_err();
+        assert_eq!(e.to_string(), "unsupported specifier '%-Q' at position 2");
+    }
+
+    #[test]
+    fn test_validate_incomplete() {
+        let e = validate("%Y%").unwrap_err();
+        assert_eq!(e.to_string(), "incomplete specifier '%' at position 3");
+    }
+
+    #[test]
+    fn test_validate_time_data() {
+        let e = validate("%Y%m%d%H").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "specifier '%H' at position 7 requires time or time zone data"
+        );
+        assert!(validate("%T").is_err());
+        assert!(validate("%c").is_err());
+        assert!(validate("%s").is_err());
+        assert!(validate("%z").is_err());
+        assert!(validate("%:z").is_err());
+        assert!(validate("%.3f").is_err());
+        assert!(validate("%p").is_err());
+    }
+
+    #[test]
+    fn test_validate_extensions() {
+        assert!(validate("%Y-%m-W%K").is_ok());
+        assert!(validate("%%K").is_ok());
+        assert!(validate("%J %E %i %N %L").is_ok());
+    }
+
+    #[test]
+    fn test_substitute() {
+        let s = substitute("%Y-%-V %%V%", |pad, spec| match (pad, spec) {
+            (Some('-'), 'V') => Some(String::from("7")),
+            (None, 'V') => Some(String::from("07")),
+            _ => None,
+        });
+        assert_eq!(s, "%Y-7 %%V%");
+        assert!(matches!(substitute("2024", |_, _| None), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_list() {
+        let date = NaiveDate::from_ymd_opt(2024, 4, 27).unwrap();
+        let formatter = DateFormatter {
+            locale: Locale::POSIX,
+            calendar: Calendar::default(),
+        };
+        let l = list(&date, &formatter);
+        assert_eq!(l.lines().count(), SPECIFIERS.len());
+        assert!(l.contains("%V   17"));
+        // week-based specifiers follow the week numbering system
+        let formatter = DateFormatter {
+            calendar: Calendar {
+                weeks: WeekSystem::Us,
+                ..Calendar::default()
+            },
+            ..formatter
+        };
+        let l = list(&NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(), &formatter);
+        assert!(l.contains("%V   53"));
+        // all listed specifiers should be accepted
+        assert!(SPECIFIERS.iter().all(|(s, _)| validate(s).is_ok()));
+    }
+}
@@ -0,0 +1,257 @@
+//! Detection of gaps and duplicates in a sequence of date tags
+//! (e.g. a daily export missing for some days)
+//!
+//! The expected sequence is made of the periods of the tag type, every
+//! step periods, from the first to the last tag or within the requested
+//! range. Tags of other periods are reported as unexpected, tags outside
+//! the range as out of range.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+
+use crate::{calendar::Calendar, datetag::DateTag, finder::Finder, scan, utils};
+
+/// arguments of the gaps subcommand
+#[derive(Debug, clap::Args)]
+pub struct GapsArgs {
+    /// Directory of the entries, using the current directory if not specified
+    #[arg(conflicts_with = "stdin")]
+    dir: Option<PathBuf>,
+
+    /// Read tags from the standard input, one per line
+    #[arg(long)]
+    stdin: bool,
+
+    /// Number of periods between expected tags
+    #[arg(long, default_value_t = 1, value_parser=clap::value_parser!(i32).range(1..))]
+    step: i32,
+
+    /// First expected period, using the first tag if not specified
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+
+    /// Last expected period, using the last tag if not specified
+    #[arg(long, value_name = "DATE")]
+    until: Option<String>,
+
+    /// Only consider entries whose name starts with the given label,
+    /// followed by the tag
+    #[arg(long)]
+    prefix: Option<String>,
+
+    /// Also fail on duplicate, unexpected and out of range tags
+    #[arg(long)]
+    strict: bool,
+}
+
+/// sequence of tags, grouped by period
+struct Sequence {
+    /// tags of each period, by period start
+    periods: BTreeMap<NaiveDate, Vec<String>>,
+}
+
+impl Sequence {
+    /// group the tags by period of the given tag type
+    fn new(tags: Vec<(NaiveDate, String)>, tag_type: DateTag, calendar: &Calendar) -> Self {
+        let mut periods: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+        for (date, tag) in tags {
+            if let Some((start, _)) = tag_type.period(&date, calendar) {
+                periods.entry(start).or_default().push(tag);
+            }
+        }
+
+        Sequence { periods }
+    }
+
+    /// expected period starts, from the first to the last given period
+    fn expected(
+        first: NaiveDate,
+        last: NaiveDate,
+        step: i32,
+        tag_type: DateTag,
+        calendar: &Calendar,
+    ) -> Result<Vec<NaiveDate>> {
+        let mut expected = Vec::new();
+        let period = |date: &NaiveDate| {
+            tag_type
+                .period(date, calendar)
+                .map(|(start, _)| start)
+                .context("wrong date offset")
+        };
+
+        let mut date = period(&first)?;
+        while date <= last {
+            expected.push(date);
+            let next = utils::checked_add_offset_with(&date, step, &tag_type, calendar)
+                .context("wrong date offset")?;
+            date = period(&next)?;
+        }
+
+        Ok(expected)
+    }
+}
+
+/// tags to check, read from a directory or the standard input
+fn tags(args: &GapsArgs, finder: &Finder) -> Result<Vec<(NaiveDate, String)>> {
+    let mut tags = Vec::new();
+
+    if args.stdin {
+        for line in io::stdin().lock().lines() {
+            let line = line?;
+            if let Some(found) = finder.find(&line) {
+                if args
+                    .prefix
+                    .as_ref()
+                    .is_none_or(|p| line[..found.start] == *p)
+                {
+                    tags.push((found.date, line.trim().to_string()));
+                }
+            }
+        }
+    } else {
+        let dir = args.dir.clone().unwrap_or_else(|| PathBuf::from("."));
+        for tagged in scan::entries(&dir, Some(0), finder)? {
+            if args.prefix.as_ref().is_none_or(|p| tagged.prefix() == p) {
+                tags.push((tagged.found.date, tagged.name));
+            }
+        }
+    }
+
+    Ok(tags)
+}
+
+/// report missing periods, duplicates and out of range tags, failing if
+/// any period is missing
+pub fn run(
+    args: &GapsArgs,
+    finder: &Finder,
+    tag_type: DateTag,
+    calendar: &Calendar,
+    parse: impl Fn(&str) -> Result<NaiveDate>,
+    label: impl Fn(&NaiveDate) -> String,
+    mut output: impl Write,
+) -> Result<()> {
+    let sequence = Sequence::new(tags(args, finder)?, tag_type, calendar);
+
+    let since = args.since.as_deref().map(&parse).transpose()?;
+    let until = args.until.as_deref().map(&parse).transpose()?;
+    let (Some(first), Some(last)) = (
+        since.or(sequence.periods.keys().next().copied()),
+        until.or(sequence.periods.keys().next_back().copied()),
+    ) else {
+        bail!("no date tag found");
+    };
+    let expected = Sequence::expected(first, last, args.step, tag_type, calendar)?;
+
+    let (mut missing, mut duplicates, mut unexpected, mut out_of_range) = (0, 0, 0, 0);
+    for start in &expected {
+        match sequence.periods.get(start).map(Vec::as_slice) {
+            None | Some([]) => {
+                writeln!(output, "missing       {}", label(start))?;
+                missing += 1;
+            }
+            Some([_]) => {}
+            Some(tags) => {
+                writeln!(
+                    output,
+                    "duplicate     {}: {}",
+                    label(start),
+                    tags.join(", ")
+                )?;
+                duplicates += 1;
+            }
+        }
+    }
+    for (start, tags) in &sequence.periods {
+        let tags = tags.join(", ");
+        if *start < expected.first().copied().unwrap_or(first) || *start > last {
+            writeln!(output, "out of range  {}: {}", label(start), tags)?;
+            out_of_range += 1;
+        } else if !expected.contains(start) {
+            writeln!(output, "unexpected    {}: {}", label(start), tags)?;
+            unexpected += 1;
+        }
+    }
+
+    writeln!(
+        output,
+        "{} expected, {} missing, {} duplicates, {} unexpected, {} out of range",
+        expected.len(),
+        missing,
+        duplicates,
+        unexpected,
+        out_of_range
+    )?;
+    output.flush()?;
+
+    if missing > 0 {
+        bail!("{} missing periods", missing);
+    }
+    if args.strict && duplicates + unexpected + out_of_range > 0 {
+        bail!(
+            "{} irregular periods",
+            duplicates + unexpected + out_of_range
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_expected() {
+        let calendar = Calendar::default();
+        assert_eq!(
+            Sequence::expected(
+                ymd(2024, 4, 27),
+                ymd(2024, 5, 1),
+                2,
+                DateTag::Daily,
+                &calendar
+            )
+            .unwrap(),
+            vec![ymd(2024, 4, 27), ymd(2024, 4, 29), ymd(2024, 5, 1)]
+        );
+        assert_eq!(
+            Sequence::expected(
+                ymd(2024, 1, 15),
+                ymd(2024, 3, 1),
+                1,
+                DateTag::Monthly,
+                &calendar
+            )
+            .unwrap(),
+            vec![ymd(2024, 1, 1), ymd(2024, 2, 1), ymd(2024, 3, 1)]
+        );
+    }
+
+    #[test]
+    fn test_sequence() {
+        let tags = vec![
+            (ymd(2024, 4, 3), "a".to_string()),
+            (ymd(2024, 4, 27), "b".to_string()),
+            (ymd(2024, 5, 1), "c".to_string()),
+        ];
+        let sequence = Sequence::new(tags, DateTag::Monthly, &Calendar::default());
+        assert_eq!(
+            sequence.periods.into_iter().collect::<Vec<_>>(),
+            vec![
+                (ymd(2024, 4, 1), vec!["a".to_string(), "b".to_string()]),
+                (ymd(2024, 5, 1), vec!["c".to_string()]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::calendar::MonthCalendar;
+
+/// day number (from 0001-01-01) of 1 Tishrei, AM 1
+const EPOCH: i64 = -1373427;
+
+/// names of months of common years, transliterated, starting from Tishrei
+const MONTHS: [&str; 12] = [
+    "Tishrei", "Cheshvan", "Kislev", "Tevet", "Shevat", "Adar", "Nisan", "Iyar", "Sivan", "Tammuz",
+    "Av", "Elul",
+];
+
+/// names of months of leap years, transliterated, starting from Tishrei
+const LEAP_MONTHS: [&str; 13] = [
+    "Tishrei", "Cheshvan", "Kislev", "Tevet", "Shevat", "Adar I", "Adar II", "Nisan", "Iyar",
+    "Sivan", "Tammuz", "Av", "Elul",
+];
+
+/// Hebrew calendar, with months numbered from Tishrei, the first month of
+/// the civil year
+pub struct Hebrew;
+
+impl Hebrew {
+    fn is_leap(year: i32) -> bool {
+        (7 * year as i64 + 1).rem_euclid(19) < 7
+    }
+
+    /// days from the epoch to the new year, according to the molad and
+    /// the postponement rules
+    fn elapsed_days(year: i32) -> i64 {
+        let months = (235 * year as i64 - 234).div_euclid(19);
+        let parts = 12084 + 13753 * months;
+        let days = 29 * months + parts.div_euclid(25920);
+
+        if (3 * (days + 1)).rem_euclid(7) < 3 {
+            days + 1
+        } else {
+            days
+        }
+    }
+
+    /// day number of 1 Tishrei of the given year
+    fn new_year(year: i32) -> i64 {
+        let (ny0, ny1, ny2) = (
+            Hebrew::elapsed_days(year - 1),
+            Hebrew::elapsed_days(year),
+            Hebrew::elapsed_days(year + 1),
+        );
+
+        let correction = if ny2 - ny1 == 356 {
+            2
+        } else if ny1 - ny0 == 382 {
+            1
+        } else {
+            0
+        };
+
+        EPOCH + ny1 + correction
+    }
+
+    fn year_days(year: i32) -> i64 {
+        Hebrew::new_year(year + 1) - Hebrew::new_year(year)
+    }
+
+    /// day number of a gregorian date, from 0001-01-01
+    fn day_number(date: &NaiveDate) -> i64 {
+        date.num_days_from_ce() as i64
+    }
+}
+
+impl MonthCalendar for Hebrew {
+    fn from_date(date: &NaiveDate) -> (i32, u32, u32) {
+        let day = Hebrew::day_number(date);
+
+        // average year length is 35975351 / 98496 days
+        let mut year = ((day - EPOCH) * 98496).div_euclid(35975351) as i32;
+        while Hebrew::new_year(year + 1) <= day {
+            year += 1;
+        }
+
+        let mut first = Hebrew::new_year(year);
+        let mut month = 1;
+        loop {
+            let days = Hebrew::month_days(year, month) as i64;
+            if day < first + days || month == Hebrew::months(year) {
+                break;
+            }
+            first += days;
+            month += 1;
+        }
+
+        (year, month, (day - first + 1) as u32)
+    }
+
+    fn to_date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        if !(1..=Hebrew::months(year)).contains(&month)
+            || !(1..=Hebrew::month_days(year, month)).contains(&day)
+        {
+            return None;
+        }
+
+        let days: i64 = (1..month).map(|m| Hebrew::month_days(year, m) as i64).sum();
+        let number = Hebrew::new_year(year) + days + day as i64 - 1;
+
+        NaiveDate::from_num_days_from_ce_opt(i32::try_from(number).ok()?)
+    }
+
+    fn months(year: i32) -> u32 {
+        if Hebrew::is_leap(year) {
+            13
+        } else {
+            12
+        }
+    }
+
+    fn month_days(year: i32, month: u32) -> u32 {
+        let leap = Hebrew::is_leap(year);
+        let days = Hebrew::year_days(year);
+
+        match (month, leap) {
+            (1, _) => 30,
+            // Cheshvan is long in complete years
+            (2, _) if days % 10 == 5 => 30,
+            (2, _) => 29,
+            // Kislev is short in deficient years
+            (3, _) if days % 10 == 3 => 29,
+            (3, _) => 30,
+            (4, _) => 29,
+            (5, _) => 30,
+            // Adar I (leap years) or Adar
+            (6, true) => 30,
+            (6, false) => 29,
+            // then months alternate between 29 and 30 days from Adar II or Nisan
+            (m, true) => 30 - (m % 2),
+            (m, false) => 29 + (m % 2),
+        }
+    }
+
+    fn month_name(year: i32, month: u32) -> &'static str {
+        if Hebrew::is_leap(year) {
+            LEAP_MONTHS[month as usize - 1]
+        } else {
+            MONTHS[month as usize - 1]
+        }
+    }
+
+    fn same_month(from: i32, month: u32, to: i32) -> u32 {
+        // Adar and Adar II hold the same feasts, later months are shifted
+        match (Hebrew::is_leap(from), Hebrew::is_leap(to)) {
+            (true, false) if month > 6 => month - 1,
+            (false, true) if month >= 6 => month + 1,
+            _ => month,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_from_date() {
+        // Rosh Hashanah 5784
+        assert_eq!(Hebrew::from_date(&ymd(2023, 9, 16)), (5784, 1, 1));
+        // 19 Nisan 5784, a leap year
+        assert_eq!(Hebrew::from_date(&ymd(2024, 4, 27)), (5784, 8, 19));
+        // 1 Nisan 5785, a common year
+        assert_eq!(Hebrew::from_date(&ymd(2025, 3, 30)), (5785, 7, 1));
+        assert_eq!(Hebrew::from_date(&ymd(2000, 1, 1)), (5760, 4, 23));
+    }
+
+    #[test]
+    fn test_year_lengths() {
+        assert_eq!(Hebrew::year_days(5784), 383);
+        assert_eq!(Hebrew::year_days(5785), 355);
+        let days: u32 = (1..=13).map(|m| Hebrew::month_days(5784, m)).sum();
+        assert_eq!(days, 383);
+        let days: u32 = (1..=12).map(|m| Hebrew::month_days(5785, m)).sum();
+        assert_eq!(days, 355);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut date = ymd(2022, 1, 1);
+        while date < ymd(2027, 1, 1) {
+            let (y, m, d) = Hebrew::from_date(&date);
+            assert_eq!(Hebrew::to_date(y, m, d), Some(date));
+            date = date.succ_opt().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_month_name() {
+        assert_eq!(Hebrew::month_name(5784, 7), "Adar II");
+        assert_eq!(Hebrew::month_name(5785, 7), "Nisan");
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::NaiveDate;
+
+use crate::calendar::{self, MonthCalendar};
+
+/// Julian day number of 1 Muharram, 1 AH (civil epoch)
+const EPOCH: i64 = 1948440;
+
+/// names of months, transliterated
+const MONTHS: [&str; 12] = [
+    "Muharram",
+    "Safar",
+    "Rabi al-Awwal",
+    "Rabi al-Thani",
+    "Jumada al-Awwal",
+    "Jumada al-Thani",
+    "Rajab",
+    "Shaban",
+    "Ramadan",
+    "Shawwal",
+    "Dhu al-Qadah",
+    "Dhu al-Hijjah",
+];
+
+/// tabular Islamic calendar, using the civil epoch and 11 leap years
+/// every 30 years
+pub struct Hijri;
+
+impl Hijri {
+    fn is_leap(year: i32) -> bool {
+        (14 + 11 * year as i64).rem_euclid(30) < 11
+    }
+
+    /// Julian day number of a Hijri date
+    fn jdn(year: i32, month: u32, day: u32) -> i64 {
+        let year = year as i64;
+        // months alternate between 30 and 29 days
+        let months = (59 * (month as i64 - 1) + 1) / 2;
+
+        day as i64 + months + (year - 1) * 354 + (3 + 11 * year).div_euclid(30) + EPOCH - 1
+    }
+}
+
+impl MonthCalendar for Hijri {
+    fn from_date(date: &NaiveDate) -> (i32, u32, u32) {
+        let jdn = calendar::jdn(date);
+
+        let year = (30 * (jdn - EPOCH) + 10646).div_euclid(10631) as i32;
+        let elapsed = jdn - 29 - Hijri::jdn(year, 1, 1);
+        // ceil(elapsed / 29.5) + 1
+        let month = (-(-2 * elapsed).div_euclid(59) + 1).clamp(1, 12) as u32;
+        let day = (jdn - Hijri::jdn(year, month, 1) + 1) as u32;
+
+        (year, month, day)
+    }
+
+    fn to_date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        if !(1..=12).contains(&month) || !(1..=Hijri::month_days(year, month)).contains(&day) {
+            return None;
+        }
+
+        calendar::date_from_jdn(Hijri::jdn(year, month, day))
+    }
+
+    fn months(_year: i32) -> u32 {
+        12
+    }
+
+    fn month_days(year: i32, month: u32) -> u32 {
+        match month {
+            12 if Hijri::is_leap(year) => 30,
+            m if m % 2 == 1 => 30,
+            _ => 29,
+        }
+    }
+
+    fn month_name(_year: i32, month: u32) -> &'static str {
+        MONTHS[month as usize - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_from_date() {
+        assert_eq!(Hijri::from_date(&ymd(622, 7, 19)), (1, 1, 1));
+        assert_eq!(Hijri::from_date(&ymd(2000, 1, 1)), (1420, 9, 24));
+        assert_eq!(Hijri::from_date(&ymd(2024, 4, 27)), (1445, 10, 18));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut date = ymd(2023, 1, 1);
+        while date < ymd(2026, 1, 1) {
+            let (y, m, d) = Hijri::from_date(&date);
+            assert_eq!(Hijri::to_date(y, m, d), Some(date));
+            date = date.succ_opt().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_to_date_invalid() {
+        assert_eq!(Hijri::to_date(1445, 2, 30), None);
+        assert_eq!(Hijri::to_date(1445, 13, 1), None);
+    }
+}
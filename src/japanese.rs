@@ -0,0 +1,109 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::datetag::DateTag;
+
+/// Japanese eras, with their letter and their first day
+const ERAS: [(char, i32, u32, u32); 5] = [
+    ('M', 1868, 10, 23),
+    ('T', 1912, 7, 30),
+    ('S', 1926, 12, 25),
+    ('H', 1989, 1, 8),
+    ('R', 2019, 5, 1),
+];
+
+/// index of the era containing the given date, none before Meiji
+fn era(date: &NaiveDate) -> Option<usize> {
+    ERAS.iter()
+        .rposition(|(_, y, m, d)| (date.year(), date.month(), date.day()) >= (*y, *m, *d))
+}
+
+/// first day of an era
+fn era_start(index: usize) -> Option<NaiveDate> {
+    let (_, y, m, d) = ERAS[index];
+    NaiveDate::from_ymd_opt(y, m, d)
+}
+
+/// value of a year specifier, given its padding modifier, with era years
+/// (e.g. R06 for %Y and 06 for %y in 2024)
+pub fn specifier(date: &NaiveDate, pad: Option<char>, spec: char) -> Option<String> {
+    let index = era(date)?;
+    let (letter, first, _, _) = ERAS[index];
+
+    let year = match spec {
+        'Y' | 'y' => date.year(),
+        'G' | 'g' => date.iso_week().year(),
+        _ => return None,
+    } - first
+        + 1;
+
+    let value = match pad {
+        Some('-') => year.to_string(),
+        Some('_') => format!("{:2}", year),
+        _ => format!("{:02}", year),
+    };
+
+    Some(match spec {
+        'Y' | 'G' => format!("{}{}", letter, value),
+        _ => value,
+    })
+}
+
+/// first and last day of yearly periods, split at the start of eras
+pub fn period(tag_type: &DateTag, date: &NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    if !matches!(tag_type, DateTag::Y | DateTag::Yearly) {
+        return None;
+    }
+
+    let index = era(date)?;
+    let first = NaiveDate::from_yo_opt(date.year(), 1)?.max(era_start(index)?);
+    let last = match ERAS.get(index + 1) {
+        Some((_, y, _, _)) if *y == date.year() => era_start(index + 1)?.pred_opt()?,
+        _ => NaiveDate::from_ymd_opt(date.year(), 12, 31)?,
+    };
+
+    Some((first, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_specifier() {
+        let date = ymd(2024, 4, 27);
+        assert_eq!(specifier(&date, None, 'Y'), Some("R06".to_string()));
+        assert_eq!(specifier(&date, Some('-'), 'Y'), Some("R6".to_string()));
+        assert_eq!(specifier(&date, None, 'y'), Some("06".to_string()));
+        assert_eq!(specifier(&date, None, 'm'), None);
+        assert_eq!(
+            specifier(&ymd(2019, 4, 30), None, 'Y'),
+            Some("H31".to_string())
+        );
+        assert_eq!(
+            specifier(&ymd(2019, 5, 1), None, 'Y'),
+            Some("R01".to_string())
+        );
+        assert_eq!(specifier(&ymd(1868, 1, 1), None, 'Y'), None);
+    }
+
+    #[test]
+    fn test_period() {
+        assert_eq!(
+            period(&DateTag::Y, &ymd(2019, 4, 30)),
+            Some((ymd(2019, 1, 1), ymd(2019, 4, 30)))
+        );
+        assert_eq!(
+            period(&DateTag::Y, &ymd(2019, 5, 1)),
+            Some((ymd(2019, 5, 1), ymd(2019, 12, 31)))
+        );
+        assert_eq!(
+            period(&DateTag::Y, &ymd(2024, 4, 27)),
+            Some((ymd(2024, 1, 1), ymd(2024, 12, 31)))
+        );
+        assert_eq!(period(&DateTag::M, &ymd(2024, 4, 27)), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Sorting and filtering of text lines (e.g. file names or release lists)
+//! by their first date tag, whatever its style and prefix
+
+use std::io::{BufRead, Write};
+
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+use crate::{calendar::Calendar, finder::Finder};
+
+/// arguments of the sort subcommand
+#[derive(Debug, clap::Args)]
+pub struct SortArgs {
+    /// Sort from the newest to the oldest tag
+    #[arg(long)]
+    reverse: bool,
+
+    /// Policy applied to lines without a date tag
+    #[arg(value_enum, long, default_value_t = SortUntagged::Last)]
+    untagged: SortUntagged,
+}
+
+/// arguments of the filter subcommand
+#[derive(Debug, clap::Args)]
+pub struct FilterArgs {
+    /// Keep lines whose tag period ends on or after the given date
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+
+    /// Keep lines whose tag period starts on or before the given date
+    #[arg(long, value_name = "DATE")]
+    until: Option<String>,
+
+    /// Policy applied to lines without a date tag
+    #[arg(value_enum, long, default_value_t = FilterUntagged::Drop)]
+    untagged: FilterUntagged,
+}
+
+/// policies applied to lines without a date tag, when sorting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortUntagged {
+    /// write them before tagged lines, in their original order
+    First,
+    /// write them after tagged lines, in their original order
+    Last,
+    /// leave them out
+    Drop,
+    /// stop with an error
+    Fail,
+}
+
+/// policies applied to lines without a date tag, when filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FilterUntagged {
+    /// write them as is
+    Keep,
+    /// leave them out
+    Drop,
+    /// stop with an error
+    Fail,
+}
+
+/// error of a line without a date tag
+fn untagged_error(number: usize, line: &str) -> anyhow::Error {
+    anyhow::anyhow!("no date tag at line {}: '{}'", number + 1, line)
+}
+
+/// write lines sorted by the period of their first tag, ties keeping
+/// their original order
+pub fn sort(
+    args: &SortArgs,
+    finder: &Finder,
+    calendar: &Calendar,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<()> {
+    let mut tagged: Vec<((NaiveDate, NaiveDate), String)> = Vec::new();
+    let mut untagged: Vec<String> = Vec::new();
+
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        let period = finder
+            .find(&line)
+            .and_then(|found| found.tag_type.period(&found.date, calendar));
+        match period {
+            Some(period) => tagged.push((period, line)),
+            None if args.untagged == SortUntagged::Fail => bail!(untagged_error(number, &line)),
+            None => untagged.push(line),
+        }
+    }
+
+    if args.reverse {
+        tagged.sort_by(|(a, _), (b, _)| b.cmp(a));
+    } else {
+        tagged.sort_by_key(|(period, _)| *period);
+    }
+
+    let tagged = tagged.into_iter().map(|(_, line)| line);
+    let lines: Vec<String> = match args.untagged {
+        SortUntagged::First => untagged.into_iter().chain(tagged).collect(),
+        SortUntagged::Last => tagged.chain(untagged).collect(),
+        SortUntagged::Drop | SortUntagged::Fail => tagged.collect(),
+    };
+    for line in lines {
+        writeln!(output, "{}", line)?;
+    }
+
+    Ok(output.flush()?)
+}
+
+/// write the lines whose first tag period overlaps the given range
+pub fn filter(
+    args: &FilterArgs,
+    finder: &Finder,
+    calendar: &Calendar,
+    parse: impl Fn(&str) -> Result<NaiveDate>,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<()> {
+    let since = args.since.as_deref().map(&parse).transpose()?;
+    let until = args.until.as_deref().map(&parse).transpose()?;
+
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        let period = finder
+            .find(&line)
+            .and_then(|found| found.tag_type.period(&found.date, calendar));
+        let keep = match period {
+            Some((start, end)) => {
+                since.is_none_or(|since| end >= since) && until.is_none_or(|until| start <= until)
+            }
+            None if args.untagged == FilterUntagged::Fail => bail!(untagged_error(number, &line)),
+            None => args.untagged == FilterUntagged::Keep,
+        };
+
+        if keep {
+            writeln!(output, "{}", line)?;
+        }
+    }
+
+    Ok(output.flush()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::LazyLock;
+
+    use clap::Parser;
+
+    use crate::utils::{self, ParseOptions};
+
+    static FINDER: LazyLock<Finder> = LazyLock::new(|| Finder::new(&ParseOptions::default()));
+
+    #[derive(Parser)]
+    enum Cli {
+        Sort(SortArgs),
+        Filter(FilterArgs),
+    }
+
+    fn run(command: &str, input: &str) -> Result<String> {
+        let mut output = Vec::new();
+        let calendar = Calendar::default();
+        let parse = |s: &str| {
+            utils::try_date_from_str_with(s, &ParseOptions::default()).map_err(anyhow::Error::msg)
+        };
+
+        match Cli::parse_from(format!("datetag {}", command).split_whitespace()) {
+            Cli::Sort(args) => sort(&args, &FINDER, &calendar, input.as_bytes(), &mut output)?,
+            Cli::Filter(args) => filter(
+                &args,
+                &FINDER,
+                &calendar,
+                parse,
+                input.as_bytes(),
+                &mut output,
+            )?,
+        }
+
+        Ok(String::from_utf8(output)?)
+    }
+
+    const LINES: &str =
+        "app_2024-04-27.log\nREADME\nrelease 2024.03\nv1_20240315\nbackup_240427.tar\n";
+
+    #[test]
+    fn test_sort() {
+        assert_eq!(
+            run("sort", LINES).unwrap(),
+            "release 2024.03\nv1_20240315\napp_2024-04-27.log\nbackup_240427.tar\nREADME\n"
+        );
+        assert_eq!(
+            run("sort --reverse --untagged first", LINES).unwrap(),
+            "README\napp_2024-04-27.log\nbackup_240427.tar\nv1_20240315\nrelease 2024.03\n"
+        );
+        assert!(run("sort --untagged fail", LINES).is_err());
+    }
+
+    #[test]
+    fn test_filter() {
+        assert_eq!(
+            run("filter --since 2024-03-20", LINES).unwrap(),
+            "app_2024-04-27.log\nrelease 2024.03\nbackup_240427.tar\n"
+        );
+        assert_eq!(
+            run("filter --until 2024-03-31 --untagged keep", LINES).unwrap(),
+            "README\nrelease 2024.03\nv1_20240315\n"
+        );
+        assert!(run("filter --untagged fail", LINES).is_err());
+    }
+}
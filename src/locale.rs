@@ -0,0 +1,165 @@
+use std::{cell::RefCell, rc::Rc};
+
+use chrono::{Locale, NaiveDate};
+
+use crate::{calendar::Calendar, formats};
+
+/// convert a locale name (e.g. 'fr_FR' or 'fr-FR') into one of the bundled locales
+pub fn try_locale_from_str(s: &str) -> Result<Locale, String> {
+    Locale::try_from(s.replace('-', "_").as_str()).map_err(|_| format!("unknown locale '{}'", s))
+}
+
+/// format a date, rendering month and weekday names in the given locale
+pub fn format(date: &NaiveDate, format: &str, locale: Locale) -> String {
+    date.format_localized(format, locale).to_string()
+}
+
+/// rendering of dates according to a locale and a calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateFormatter {
+    pub locale: Locale,
+    pub calendar: Calendar,
+}
+
+impl DateFormatter {
+    /// format a date, expanding datetag and calendar-specific specifiers first
+    pub fn format(&self, date: &NaiveDate, format: &str) -> String {
+        let format =
+            formats::substitute(format, |pad, spec| self.calendar.specifier(date, pad, spec));
+
+        self::format(date, &format, self.locale)
+    }
+}
+
+/// lowercase month names, along with their number
+type MonthNames = Rc<Vec<(String, u32)>>;
+
+thread_local! {
+    /// month names of the last requested locale
+    static MONTH_NAMES: RefCell<Option<(Locale, MonthNames)>> = const { RefCell::new(None) };
+}
+
+/// lowercase month names (full and abbreviated) of a locale, along with
+/// their number, sorted from the longest one
+///
+/// English names are always included.
+fn month_names(locale: Locale) -> MonthNames {
+    MONTH_NAMES.with(|cache| {
+        if let Some((l, names)) = cache.borrow().as_ref() {
+            if *l == locale {
+                return names.clone();
+            }
+        }
+
+        let mut names = Vec::new();
+        for l in [locale, Locale::POSIX] {
+            for month in 1..=12 {
+                let date = NaiveDate::from_ymd_opt(2000, month, 1).unwrap();
+                for spec in ["%B", "%b"] {
+                    let name = format(&date, spec, l).trim_end_matches('.').to_lowercase();
+                    if !name.is_empty() && !names.contains(&(name.clone(), month)) {
+                        names.push((name, month));
+                    }
+                }
+            }
+        }
+        names.sort_by_key(|(n, _)| std::cmp::Reverse(n.chars().count()));
+
+        let names = Rc::new(names);
+        *cache.borrow_mut() = Some((locale, names.clone()));
+        names
+    })
+}
+
+/// look for a month name in the given string
+///
+/// Returns the month number, along with the lowercase string without
+/// the month name. Names within other words (e.g. 'mar' in 'summary')
+/// are ignored.
+pub fn find_month(s: &str, locale: Locale) -> Option<(u32, String)> {
+    let lower = s.to_lowercase();
+    let is_word = |i: usize, name: &str| {
+        !lower[..i]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphabetic)
+            && !lower[i + name.len()..]
+                .chars()
+                .next()
+                .is_some_and(char::is_alphabetic)
+    };
+
+    month_names(locale).iter().find_map(|(name, month)| {
+        lower
+            .match_indices(name.as_str())
+            .find(|(i, _)| is_word(*i, name))
+            .map(|(i, _)| {
+                (
+                    *month,
+                    format!("{} {}", &lower[..i], &lower[i + name.len()..]),
+                )
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weeks::WeekSystem;
+
+    #[test]
+    fn test_try_locale_from_str() {
+        assert_eq!(try_locale_from_str("fr_FR").unwrap(), Locale::fr_FR);
+        assert_eq!(try_locale_from_str("de-DE").unwrap(), Locale::de_DE);
+        assert!(try_locale_from_str("xx_YY").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        let date = NaiveDate::from_ymd_opt(2024, 4, 27).unwrap();
+        assert_eq!(format(&date, "%B-%Y", Locale::fr_FR), "avril-2024");
+        assert_eq!(format(&date, "%b%Y", Locale::POSIX), "Apr2024");
+        assert_eq!(format(&date, "%A %d", Locale::it_IT), "sabato 27");
+    }
+
+    #[test]
+    fn test_date_formatter() {
+        let date = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let f = DateFormatter {
+            locale: Locale::fr_FR,
+            calendar: Calendar {
+                weeks: WeekSystem::Us,
+                ..Calendar::default()
+            },
+        };
+        assert_eq!(f.format(&date, "%b %G-W%V"), "déc. 2024-W53");
+        assert_eq!(f.format(&date, "%Y-%m-W%K %%K"), "2024-12-W5 %K");
+    }
+
+    #[test]
+    fn test_find_month() {
+        let (m, rest) = find_month("Avril-2024", Locale::fr_FR).unwrap();
+        assert_eq!(m, 4);
+        assert_eq!(rest, " -2024");
+        // english names are always available
+        let (m, _) = find_month("27-Sep-2024", Locale::fr_FR).unwrap();
+        assert_eq!(m, 9);
+        // full names take precedence over abbreviated ones
+        let (m, rest) = find_month("juillet 2024", Locale::fr_FR).unwrap();
+        assert_eq!(m, 7);
+        assert_eq!(rest, "  2024");
+        assert!(find_month("2024-04", Locale::fr_FR).is_none());
+    }
+
+    #[test]
+    fn test_find_month_within_words() {
+        assert!(find_month("summary_2024", Locale::POSIX).is_none());
+        assert!(find_month("mayday-2024", Locale::POSIX).is_none());
+        assert!(find_month("decoder_12", Locale::POSIX).is_none());
+        let (m, rest) = find_month("summary_mar2024", Locale::POSIX).unwrap();
+        assert_eq!(m, 3);
+        assert_eq!(rest, "summary_ 2024");
+        let (m, _) = find_month("27Apr2024", Locale::POSIX).unwrap();
+        assert_eq!(m, 4);
+    }
+}
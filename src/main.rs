@@ -11,8 +11,17 @@
 //!
 //! datetag references belong to one of the following types:
 //!  * YEARLY (i.e. match the format '%Y')
+//!  * QUARTERLY (i.e. match the format '%YQ%q')
+//!  * WEEKLY (i.e. match the format '%G%V')
+//!  * WEEK-DAY (i.e. match the format '%G%V%u')
+//!  * WEEK-OF-MONTH (i.e. match the format '%Y%mW%K', days 1-7 being week 1)
+//!  * ORDINAL (i.e. match the format '%Y%j')
 //!  * MONTHLY (i.e. match the format '%Y%m')
 //!  * DAILY (i.e. match the format '%Y%m&d')
+//!  * EPOCH-DAY (i.e. match the format '%E', days since the epoch)
+//!  * EPOCH-SECONDS (i.e. match the format '%i')
+//!  * BASE36 (i.e. match the format '%N', base 36 days since the epoch)
+//!  * HEX (i.e. match the format '%L', hexadecimal days since the epoch)
 //!
 //! datetag refereces can be plain formated (e.g. 20240424) or
 //! formatted using a separator character (i.e. '.', ':' or '/'):
@@ -20,12 +29,82 @@
 //!  * 2024-04-24
 //!  * 2024/04/24
 //!
+//! Any other separator and field order can be used as well
+//! (e.g. 2024_04_24, 24.04.2024 or 04/24/2024), as well as two-digit
+//! years (e.g. 240424 or 2417), ISO 8601 week dates (e.g. 2024-W17-3)
+//! and month names (e.g. Apr-2024), using the requested locale
+//! (e.g. avril-2024).
+//!
+//! Weekly tags follow ISO 8601 weeks by default, US, Sunday-start
+//! and broadcast calendar weeks are available as well.
+//!
+//! Retail fiscal calendars (4-4-5, 4-5-4 or 5-4-4 weeks per quarter)
+//! replace years, quarters, months and weeks with fiscal ones
+//! (e.g. FY24-P04-W2).
+//!
+//! Julian day numbers, Japanese era years (e.g. R06.04.27), as well as
+//! tabular Islamic (Hijri) and Hebrew dates are available for tags of
+//! other calendars.
+//!
+//! Partition paths of data lakes are supported as well, either as
+//! nested directories (e.g. 2024/04/24) or as Hive key=value segments
+//! (e.g. year=2024/month=04/day=24), whose directory trees can be
+//! created for a single date or a range of dates.
+//!
+//! Tags embedded in package versions can be checked against the grammar
+//! of SemVer, PEP 440, Debian, RPM or OCI image tags, and normalized
+//! where possible (e.g. 2024.4.27 for SemVer).
+//!
+//! The calver subcommand prints the next CalVer release version
+//! (e.g. 24.04.2 for the YY.0M.MICRO scheme), following the existing
+//! versions read from a file, the standard input or git tags.
+//!
+//! Unique tags append a counter per tag (e.g. LAB_20240427_02), either
+//! stored in a file-locked state file, safe for concurrent CI jobs, or
+//! derived from the names of a directory or from git tags.
+//!
+//! The scan subcommand lists the entries of a directory tree whose name
+//! contains a date tag, in any style, grouped by year, quarter, month,
+//! week or day.
+//!
+//! The prune subcommand applies grandfather-father-son retention policies
+//! to date tagged entries (e.g. backup_20240427.tar), keeping the newest
+//! entry of the most recent days, weeks, months, quarters or years.
+//!
+//! The rename subcommand adds a date tag to the names of files, or
+//! replaces their existing one, using their modification time, a date
+//! embedded in their content or a fixed reference date. Renames are
+//! recorded in a journal, so that they can be undone.
+//!
+//! The normalize subcommand rewrites the date tags of a directory tree,
+//! whatever their style, to a single style and tag type, refusing
+//! ambiguous conversions (e.g. monthly to daily tags).
+//!
+//! The archive subcommand moves or copies files into dated folders
+//! (e.g. archive/2024/04 or archive/2024-W17), skipping, renaming or
+//! overwriting files already archived.
+//!
+//! The sort and filter subcommands order lines read from the standard
+//! input by their first date tag, or keep the ones within a date range.
+//!
+//! The gaps subcommand checks that a sequence of date tags (e.g. daily
+//! exports) holds a single tag per period, failing when periods are
+//! missing, so that it can be used as a CI or cron health check.
+//!
+//! The extract subcommand prints the date tags found in any text
+//! (e.g. release notes), with their position, date and period, ignoring
+//! numbers that only look like tags (e.g. phone numbers) according to
+//! the requested strictness.
+//!
+//! Encoded tags (e.g. days since the epoch, in base 36) are short enough
+//! for cache keys, they can be decoded back from '@' dates (e.g. @fb4).
+//!
 //! It is possible to add/subtract an offset to specific datetag.
 //! The offset value should be expressed in:
 //!  * years
 //!  * months
 //!  * days
-//! 
+//!
 //! depending on the datetag type.
 //!
 //! In case of generation of a single datetag then the offset will
@@ -36,20 +115,79 @@
 //!
 //! It is possible to obtain the NOW datetag or provide the current
 //! reference date.
+//!
+//! Reference dates can also be read from the standard input, one per
+//! line (or from a CSV column), to convert large batches of dates.
+//!
+//! Generated datetags can be displayed as plain text or as structured
+//! records (JSON, JSON lines, CSV or TSV) including tag metadata.
+//!
+//! Richer layouts can be composed using a template, mixing date
+//! references with sequence indexes, environment variables, host and
+//! user names or git references (e.g. '{env:PROJECT}-{date}-{seq:03}').
 
+mod archive;
+mod batch;
+mod calendar;
+mod calver;
 mod datestyle;
 mod datetag;
+mod epoch;
+mod extract;
+mod finder;
+mod formats;
+mod gaps;
+mod hebrew;
+mod hijri;
+mod japanese;
+mod lines;
+mod locale;
+mod normalize;
+mod output;
+mod prune;
+mod rename;
+mod retail;
+mod scan;
+mod target;
+mod template;
 mod texts;
+mod unique;
 mod utils;
+mod weeks;
 
-use std::{fs, path::PathBuf};
+use std::{
+    env, fs,
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
 
-use anyhow::{Context, Result};
-use chrono::{DateTime, Local, NaiveDate, Utc};
-use clap::Parser;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local, Locale, NaiveDate, Utc};
+use clap::{Parser, Subcommand};
 
-use datestyle::DateStyle;
+use archive::ArchiveArgs;
+use batch::{Entry, Layout, OnError};
+use calendar::{Calendar, CalendarSystem};
+use calver::CalverArgs;
+use datestyle::{DateStyle, Field, FieldOrder, MonthRepr, StylePreset};
 use datetag::DateTag;
+use epoch::Encoding;
+use extract::ExtractArgs;
+use finder::Finder;
+use gaps::GapsArgs;
+use lines::{FilterArgs, SortArgs};
+use locale::DateFormatter;
+use normalize::NormalizeArgs;
+use output::{OutputFormat, TagRecord, TagWriter};
+use prune::PruneArgs;
+use rename::RenameArgs;
+use retail::{RetailCalendar, RetailPattern, YearEnd, YearName};
+use scan::ScanArgs;
+use target::Target;
+use template::Template;
+use unique::Counters;
+use utils::ParseOptions;
+use weeks::WeekSystem;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -62,17 +200,77 @@ use datetag::DateTag;
 )]
 struct Args {
     /// Reference date, using today is not specified (e.g. 'yyyymmdd', 'yyyymm',
-    /// 'yyyy', allowed field separators: '.-/:').
-    #[arg(value_parser=utils::try_date_from_str)]
-    date: Option<NaiveDate>,
+    /// 'yyyy', allowed field separators: '.-/:'), fields are also read
+    /// according to --style (e.g. 'dd.mm.yyyy' or 'yymmdd').
+    date: Option<String>,
 
-    /// Tag type [d | m | y | daily | monthly | yearly]
+    /// Tag type [d | o | wd | w | mw | m | q | y | ed | es | b36 | x | daily | ordinal |
+    /// week-day | weekly | week-of-month | monthly | quarterly | yearly | epoch-day |
+    /// epoch-seconds | base36 | hex]
     #[arg(value_enum, short, long, default_value_t = DateTag::M)]
     tag_type: DateTag,
 
-    /// Date tag style
-    #[arg(value_enum, short, long, default_value_t = DateStyle::Plain)]
-    style: DateStyle,
+    /// Date tag style [default: plain, or retail with '--calendar retail']
+    #[arg(value_enum, short, long)]
+    style: Option<StylePreset>,
+
+    /// Date field separator, override --style separator (e.g. '_')
+    #[arg(long)]
+    separator: Option<String>,
+
+    /// Date field order, override --style order
+    #[arg(value_enum, long)]
+    order: Option<FieldOrder>,
+
+    /// Use two-digit years (e.g. '240427'), override --style year width
+    #[arg(long)]
+    short_year: bool,
+
+    /// Month representation, override --style months
+    #[arg(value_enum, long)]
+    months: Option<MonthRepr>,
+
+    /// Key name of a Hive partition field (e.g. 'month=mo'), with '--style hive'
+    #[arg(long, value_name = "FIELD=KEY", value_parser=datestyle::try_hive_key_from_str)]
+    hive_key: Vec<(Field, String)>,
+
+    /// Locale of month and weekday names (e.g. 'fr_FR', 'de_DE')
+    #[arg(long, default_value_t = Locale::POSIX, value_parser=locale::try_locale_from_str)]
+    locale: Locale,
+
+    /// Week numbering system of weekly tags
+    #[arg(value_enum, long, default_value_t = WeekSystem::Iso)]
+    week_system: WeekSystem,
+
+    /// Calendar system of date tags
+    #[arg(value_enum, long, default_value_t = CalendarSystem::Gregorian)]
+    calendar: CalendarSystem,
+
+    /// Weeks of the periods of each retail quarter
+    #[arg(value_enum, long, default_value_t = RetailPattern::P454)]
+    retail_pattern: RetailPattern,
+
+    /// Rule used to find the last Saturday of retail fiscal years
+    #[arg(value_enum, long, default_value_t = YearEnd::Nearest)]
+    retail_year_end: YearEnd,
+
+    /// Month of the end of retail fiscal years (1-12)
+    #[arg(long, default_value_t = 1, value_parser=clap::value_parser!(u32).range(1..=12))]
+    retail_end_month: u32,
+
+    /// Calendar year retail fiscal years are named after, the start year
+    /// following the NRF convention (e.g. FY24 from February 2024)
+    #[arg(value_enum, long, default_value_t = YearName::Start)]
+    retail_year_name: YearName,
+
+    /// First day of encoded tags, as a reference date [default: 1970-01-01]
+    #[arg(long, value_name = "DATE")]
+    epoch: Option<String>,
+
+    /// Two-digit reference years below this value belong to the 21st century,
+    /// the others to the 20th century
+    #[arg(long, default_value_t = utils::DEFAULT_CENTURY_PIVOT, value_parser=clap::value_parser!(u32).range(0..=100))]
+    century_pivot: u32,
 
     /// Tag prefix (e.g. 'LAB_202404')
     #[arg(short, long)]
@@ -94,6 +292,15 @@ struct Args {
     #[arg(short, long, value_parser=clap::value_parser!(u8).range(1..))]
     repeat: Option<u8>,
 
+    /// Generate date tags up to the given date (included), stepping by the
+    /// offset value (default: 1)
+    #[arg(short, long, conflicts_with_all = ["repeat", "stdin"])]
+    until: Option<String>,
+
+    /// Create the directory tree of each generated tag under the given root
+    #[arg(long, value_name = "ROOT")]
+    mkdir: Option<PathBuf>,
+
     /// Append an end-of-line to each generated tag
     #[arg(short, long, default_value_t = false)]
     new_line: bool,
@@ -101,20 +308,261 @@ struct Args {
     /// Custom date reference format string, override --style value
     #[arg(long)]
     format: Option<String>,
+
+    /// List supported format specifiers, with examples for the reference date
+    #[arg(long)]
+    list_formats: bool,
+
+    /// Read reference dates from standard input, one per line
+    #[arg(long, conflicts_with_all = ["date", "file", "repeat"])]
+    stdin: bool,
+
+    /// Read reference dates from the given CSV column (starting from 1)
+    #[arg(long, requires = "stdin", value_parser=clap::value_parser!(u32).range(1..))]
+    column: Option<u32>,
+
+    /// CSV field delimiter used with --column
+    #[arg(long, requires = "column", default_value_t = ',')]
+    delimiter: char,
+
+    /// Policy applied to invalid reference dates read from standard input
+    #[arg(value_enum, long, requires = "stdin", default_value_t = OnError::Fail)]
+    on_error: OnError,
+
+    /// Tag template with placeholders (e.g. '{env:PROJECT}-{date:%Y%m}-{seq:03}'),
+    /// override --prefix and --suffix values
+    #[arg(long, conflicts_with_all = ["prefix", "suffix"])]
+    template: Option<String>,
+
+    /// Check and normalize tags against the version grammar of a packaging
+    /// ecosystem
+    #[arg(value_enum, long)]
+    target: Option<Target>,
+
+    /// Append a per-tag counter to generated tags (e.g. 'LAB_20240427_02'),
+    /// stored in a state file by default
+    #[arg(long)]
+    unique: bool,
+
+    /// State file of unique tag counters [default: .datetag-state]
+    #[arg(long, value_name = "FILE", requires = "unique")]
+    state: Option<PathBuf>,
+
+    /// Derive unique tag counters from the entries of the given directory
+    #[arg(
+        long,
+        value_name = "DIR",
+        requires = "unique",
+        conflicts_with = "state"
+    )]
+    unique_dir: Option<PathBuf>,
+
+    /// Derive unique tag counters from git tags
+    #[arg(long, requires = "unique", conflicts_with_all = ["state", "unique_dir"])]
+    unique_git: bool,
+
+    /// Output format, structured formats include tag metadata
+    #[arg(value_enum, long, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print the next CalVer version of the reference date (e.g. 24.04.2),
+    /// following existing versions
+    Calver(CalverArgs),
+    /// List the entries of a directory tree whose name contains a date tag,
+    /// grouped by period
+    Scan(ScanArgs),
+    /// Keep the newest date tagged entries of recent periods (e.g. 7 daily
+    /// and 12 monthly backups), pruning the others
+    Prune(PruneArgs),
+    /// Add a date tag to the names of files (e.g. '20240427_report.pdf'),
+    /// or replace their existing tag, using --tag-type and --style
+    Rename(RenameArgs),
+    /// Rewrite the date tags of a directory tree to the --tag-type and
+    /// --style values (e.g. '2024.04.03_x' into '2024-04-03_x')
+    Normalize(NormalizeArgs),
+    /// Move or copy files into folders named after their date, using
+    /// --tag-type and --style (e.g. '<ROOT>/2024/04/report.pdf')
+    Archive(ArchiveArgs),
+    /// Sort lines read from standard input by their first date tag
+    Sort(SortArgs),
+    /// Keep lines read from standard input whose first date tag belongs to
+    /// a range of dates
+    Filter(FilterArgs),
+    /// Report missing periods and duplicates in a sequence of date tags,
+    /// failing if any period is missing
+    Gaps(GapsArgs),
+    /// Print the date tags found in files or standard input, with their
+    /// position, date and period
+    Extract(ExtractArgs),
 }
 
 fn main() -> Result<()> {
     // parse command-line parameters
     let args = Args::parse();
 
+    // retrieve date tag style, applying any override
+    let mut style = DateStyle::from(args.style.unwrap_or(match args.calendar {
+        CalendarSystem::Retail => StylePreset::Retail,
+        _ => StylePreset::Plain,
+    }));
+    if let Some(separator) = args.separator {
+        style.separator = separator;
+    }
+    if let Some(order) = args.order {
+        style.order = order;
+    }
+    if args.short_year {
+        style.short_year = true;
+    }
+    if let Some(months) = args.months {
+        style.month = months;
+    }
+    if !args.hive_key.is_empty() {
+        let Some(keys) = style.hive.as_mut() else {
+            bail!("'--hive-key' requires '--style hive'");
+        };
+        for (field, key) in &args.hive_key {
+            keys.set(*field, key.clone());
+        }
+    }
+
+    // read reference dates according to the date tag style and locale
+    let mut options =
+        ParseOptions::from_style(&style, args.century_pivot, args.locale, args.week_system);
+
+    // retrieve the first day of encoded tags, '@' dates being decoded
+    // according to the encoding of the tag type
+    let epoch = match &args.epoch {
+        Some(epoch) => utils::try_date_from_str_with(epoch, &options)
+            .map_err(|e| anyhow!("wrong epoch '{}': {}", epoch, e))?,
+        None => epoch::unix_epoch(),
+    };
+    options.epoch = epoch;
+    options.encoding = args.tag_type.encoding().unwrap_or(Encoding::Days);
+    options.weekly = matches!(args.tag_type, DateTag::W | DateTag::Weekly);
+
+    // retrieve the calendar used to compute tag fields, periods and offsets
+    let calendar = Calendar {
+        weeks: args.week_system,
+        system: args.calendar,
+        retail: RetailCalendar {
+            pattern: args.retail_pattern,
+            year_end: args.retail_year_end,
+            end_month: args.retail_end_month,
+            year_name: args.retail_year_name,
+        },
+        epoch,
+    };
+
+    // render dates according to the locale and calendar
+    let formatter = DateFormatter {
+        locale: args.locale,
+        calendar,
+    };
+
+    // check custom format string before generating any tag
+    if let Some(format) = &args.format {
+        formats::validate(format).with_context(|| "wrong date format".to_string())?;
+    }
+
+    // run the requested subcommand, if any
+    if let Some(command) = &args.command {
+        let parse = |s: &str| {
+            utils::try_date_from_str_with(s, &options)
+                .map_err(|e| anyhow!("wrong reference date '{}': {}", s, e))
+        };
+        let today = Local::now().naive_local().date();
+        let finder = Finder::new(&options);
+
+        // format of the tags written by subcommands
+        let format = match &args.format {
+            Some(format) => format.clone(),
+            None => args.tag_type.get_format(&style),
+        };
+
+        match command {
+            Command::Calver(calver) => {
+                let version = calver::run(calver, parse, today, &formatter)?;
+                let version = match args.target {
+                    Some(target) => target.normalize(&version).with_context(|| {
+                        format!("'{}' is not a valid {}", version, target.label())
+                    })?,
+                    None => version,
+                };
+                println!("{}", version);
+            }
+            Command::Scan(scan) => {
+                print!("{}", scan::run(scan, &finder, parse, &formatter, &style)?);
+            }
+            Command::Prune(prune) => {
+                print!("{}", prune::run(prune, &finder, &calendar)?);
+            }
+            Command::Rename(rename) => {
+                let reference = |date: &NaiveDate| formatter.format(date, &format);
+                print!("{}", rename::run(rename, &finder, parse, reference)?);
+            }
+            Command::Normalize(normalize) => {
+                let report =
+                    normalize::run(normalize, &finder, args.tag_type, &format, &formatter)?;
+                print!("{}", report);
+            }
+            Command::Archive(archive) => {
+                let folder = |date: &NaiveDate| formatter.format(date, &format);
+                print!("{}", archive::run(archive, &finder, parse, folder)?);
+            }
+            Command::Sort(sort) => {
+                let output = BufWriter::new(io::stdout().lock());
+                lines::sort(sort, &finder, &calendar, io::stdin().lock(), output)?;
+            }
+            Command::Filter(filter) => {
+                let output = BufWriter::new(io::stdout().lock());
+                lines::filter(
+                    filter,
+                    &finder,
+                    &calendar,
+                    parse,
+                    io::stdin().lock(),
+                    output,
+                )?;
+            }
+            Command::Gaps(gaps) => {
+                let label = |date: &NaiveDate| formatter.format(date, &format);
+                let output = io::stdout().lock();
+                gaps::run(
+                    gaps,
+                    &finder,
+                    args.tag_type,
+                    &calendar,
+                    parse,
+                    label,
+                    output,
+                )?;
+            }
+            Command::Extract(extract) => {
+                let output = BufWriter::new(io::stdout().lock());
+                extract::run(extract, &finder, &calendar, output)?;
+            }
+        }
+        return Ok(());
+    }
+
     // retrieve reference date
     let mut date = if let Some(file) = args.file {
         // retrieve reference date from specified file metadata
         DateTime::<Utc>::from(fs::metadata(file)?.modified()?).date_naive()
+    } else if let Some(date) = args.date {
+        // retrieve reference date from date args
+        utils::try_date_from_str_with(&date, &options)
+            .map_err(|e| anyhow!("wrong reference date '{}': {}", date, e))?
     } else {
-        // retrieve reference date from date args, otherwise use current date
-        args.date
-            .unwrap_or_else(|| Local::now().naive_local().date())
+        // otherwise use current date
+        Local::now().naive_local().date()
     };
 
     // retrieve date tag prefix label
@@ -126,38 +574,213 @@ fn main() -> Result<()> {
     // retrieve repeat value
     let repeat = args.repeat.unwrap_or(1);
 
+    // retrieve the last date of the range, if any
+    let until = args
+        .until
+        .as_deref()
+        .map(|until| {
+            utils::try_date_from_str_with(until, &options)
+                .map_err(|e| anyhow!("wrong until date '{}': {}", until, e))
+        })
+        .transpose()?;
+
+    // list supported format specifiers
+    if args.list_formats {
+        print!("{}", formats::list(&date, &formatter));
+        return Ok(());
+    }
+
+    // retrieve date tag template, resolving any environment-related value
+    let template = args
+        .template
+        .as_deref()
+        .map(|t| Template::new(t, |name| env::var(name).ok()))
+        .transpose()?;
+
     // retrieve date reference format string
-    let format = args
-        .format
-        .unwrap_or(String::from(args.tag_type.get_format(args.style)));
+    let format = match &args.format {
+        Some(format) => format.clone(),
+        None if args.calendar == CalendarSystem::JulianDay => {
+            if !matches!(args.tag_type, DateTag::D | DateTag::Daily) {
+                bail!("'--calendar julian-day' requires daily tags");
+            }
+            "%J".to_string()
+        }
+        None => args.tag_type.get_format(&style),
+    };
+
+    // retrieve date tag style name, unless overridden by a custom format
+    let style =
+        (args.format.is_none() && args.tag_type.encoding().is_none()).then(|| style.to_string());
+
+    // retrieve the counters of unique tags, if requested
+    let mut counters = args.unique.then(|| {
+        Counters::new(if let Some(dir) = &args.unique_dir {
+            unique::Source::Dir(dir.clone())
+        } else if args.unique_git {
+            unique::Source::Git
+        } else {
+            unique::Source::State(
+                args.state
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(unique::DEFAULT_STATE_FILE)),
+            )
+        })
+    });
+
+    // build the tag record related to a date
+    let mut record = |index: usize, date: NaiveDate| {
+        let reference = formatter.format(&date, &format);
+        let (period_start, period_end) = args
+            .tag_type
+            .period(&date, &calendar)
+            .with_context(|| "wrong date offset".to_string())?;
+
+        let tag = match &template {
+            Some(t) => t.render(
+                &date,
+                (period_start, period_end),
+                index,
+                &format,
+                &formatter,
+            ),
+            None => format!("{}{}{}", prefix, reference, suffix),
+        };
+
+        // append the next counter of the tag, if requested
+        let tag = match counters.as_mut() {
+            Some(counters) => counters.unique(&tag)?,
+            None => tag,
+        };
+
+        // follow the version grammar of the target ecosystem, if any
+        let tag = match args.target {
+            Some(target) => target
+                .normalize(&tag)
+                .with_context(|| format!("'{}' is not a valid {}", tag, target.label()))?,
+            None => tag,
+        };
+
+        Ok::<_, anyhow::Error>(TagRecord {
+            tag,
+            prefix: prefix.clone(),
+            date: reference,
+            suffix: suffix.clone(),
+            tag_type: args.tag_type.name(),
+            style: style.clone(),
+            format: format.clone(),
+            period_start,
+            period_end,
+            index,
+        })
+    };
+
+    // convert reference dates read from standard input
+    if args.stdin {
+        let layout = match args.column {
+            Some(column) => Layout::Csv {
+                column: column as usize - 1,
+                delimiter: batch::delimiter_from_char(args.delimiter)?,
+            },
+            None => Layout::Lines,
+        };
+
+        let mut writer = TagWriter::new(BufWriter::new(io::stdout().lock()), args.output, true)?;
+
+        // invalid entries can be copied as is only to plain text output
+        if args.on_error == OnError::Passthrough && !writer.is_text() {
+            bail!("'--on-error passthrough' requires '--output text'");
+        }
+
+        // apply date offset, failed offsets following the error policy
+        let offset = |date: &NaiveDate| {
+            utils::checked_add_offset_with(date, args.offset, &args.tag_type, &calendar)
+        };
+        let entries = batch::entries(io::stdin().lock(), layout, options, args.on_error, offset);
+
+        for (index, entry) in entries.enumerate() {
+            match entry? {
+                Entry::Date(date) => {
+                    let record = record(index, date)?;
+                    writer.write(&record)?;
+                    make_dir(args.mkdir.as_deref(), &record.tag)?;
+                }
+                Entry::Passthrough(raw) => writer.write_raw(&raw)?,
+            }
+        }
+
+        return writer.finish();
+    }
+
+    // generate date tags over a range of dates
+    if let Some(until) = until {
+        // step towards the last date, one period at a time by default
+        let step = match args.offset {
+            0 if until < date => -1,
+            0 => 1,
+            offset => offset,
+        };
+
+        let mut writer = TagWriter::new(io::stdout().lock(), args.output, true)?;
+
+        let mut index = 0;
+        loop {
+            let (start, end) = args
+                .tag_type
+                .period(&date, &calendar)
+                .with_context(|| "wrong date offset".to_string())?;
+            if (step > 0 && start > until) || (step < 0 && end < until) {
+                break;
+            }
+
+            let record = record(index, date)?;
+            writer.write(&record)?;
+            make_dir(args.mkdir.as_deref(), &record.tag)?;
+
+            date = utils::checked_add_offset_with(&date, step, &args.tag_type, &calendar)
+                .with_context(|| "wrong date offset".to_string())?;
+            index += 1;
+        }
+
+        return writer.finish();
+    }
 
     // with no repetitions, apply offset immediately
     if repeat == 1 {
         // apply date offset
-        date = utils::checked_add_offset(&date, args.offset, &args.tag_type)
+        date = utils::checked_add_offset_with(&date, args.offset, &args.tag_type, &calendar)
             .with_context(|| "wrong date offset".to_string())?;
     }
 
+    // append an end-of-line if requested or needed
+    let mut writer = TagWriter::new(
+        io::stdout().lock(),
+        args.output,
+        args.new_line || repeat > 1,
+    )?;
+
     // generate date tags
-    for _ in 1..=repeat {
+    for index in 0..repeat as usize {
         // display date tag
-        print!(
-            "{}{}{}",
-            prefix,
-            //date.format(args.tag_type.get_format(args.style)),
-            date.format(&format),
-            suffix
-        );
-
-        // append an end-of-line if requested or needed
-        if args.new_line || repeat > 1 {
-            println!();
-        }
+        let record = record(index, date)?;
+        writer.write(&record)?;
+        make_dir(args.mkdir.as_deref(), &record.tag)?;
 
         // apply date offset for the next repetition
-        date = utils::checked_add_offset(&date, args.offset, &args.tag_type)
+        date = utils::checked_add_offset_with(&date, args.offset, &args.tag_type, &calendar)
             .with_context(|| "wrong date offset".to_string())?;
     }
 
+    writer.finish()
+}
+
+/// create the directory tree of a date tag under the given root, if any
+fn make_dir(root: Option<&Path>, tag: &str) -> Result<()> {
+    if let Some(root) = root {
+        let path = root.join(tag);
+        fs::create_dir_all(&path)
+            .with_context(|| format!("cannot create directory '{}'", path.display()))?;
+    }
+
     Ok(())
 }
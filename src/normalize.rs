@@ -0,0 +1,191 @@
+use std::{fmt::Write, path::PathBuf};
+
+use anyhow::{bail, Result};
+
+use crate::{
+    datetag::DateTag,
+    finder::Finder,
+    locale::DateFormatter,
+    rename::{self, DEFAULT_JOURNAL},
+    scan::{self, Tagged},
+};
+
+/// arguments of the normalize subcommand
+#[derive(Debug, clap::Args)]
+pub struct NormalizeArgs {
+    /// Directory to normalize, using the current directory if not specified
+    dir: Option<PathBuf>,
+
+    /// Maximum depth of the normalized subdirectories (0: only the directory entries)
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Journal of applied renames, reverted by 'rename --undo'
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_JOURNAL)]
+    journal: PathBuf,
+
+    /// Rename entries, instead of previewing the changes
+    #[arg(long)]
+    apply: bool,
+}
+
+/// new name of an entry, with its tag rewritten to the target tag type
+///
+/// Conversions are refused when the target period does not contain the
+/// tagged one (e.g. monthly to daily tags, or weekly to monthly tags).
+fn normalized(
+    tagged: &Tagged,
+    tag_type: DateTag,
+    format: &str,
+    formatter: &DateFormatter,
+) -> Result<String, String> {
+    let found = &tagged.found;
+    let calendar = &formatter.calendar;
+
+    let out_of_range = || "date out of range".to_string();
+    let (start, end) = found
+        .tag_type
+        .period(&found.date, calendar)
+        .ok_or_else(out_of_range)?;
+    let (target_start, target_end) = tag_type.period(&start, calendar).ok_or_else(out_of_range)?;
+    if start < target_start || end > target_end {
+        return Err(format!(
+            "cannot convert {} tags into {} tags",
+            found.tag_type.name(),
+            tag_type.name()
+        ));
+    }
+
+    Ok(format!(
+        "{}{}{}",
+        tagged.prefix(),
+        formatter.format(&start, format),
+        tagged.suffix()
+    ))
+}
+
+/// rewrite the tags of a directory tree to the target tag type and format
+pub fn run(
+    args: &NormalizeArgs,
+    finder: &Finder,
+    tag_type: DateTag,
+    format: &str,
+    formatter: &DateFormatter,
+) -> Result<String> {
+    let dir = args.dir.clone().unwrap_or_else(|| PathBuf::from("."));
+
+    // rename nested entries before their parent directories
+    let mut entries = scan::entries(&dir, args.max_depth, finder)?;
+    entries.sort_by_key(|t| std::cmp::Reverse(t.path.components().count()));
+
+    let mut report = String::new();
+    let mut renames: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut refused = 0;
+    for tagged in &entries {
+        let name = match normalized(tagged, tag_type, format, formatter) {
+            Ok(name) if name == tagged.name => continue,
+            Ok(name) => name,
+            Err(reason) => {
+                writeln!(report, "! {} ({})", tagged.path.display(), reason)?;
+                refused += 1;
+                continue;
+            }
+        };
+
+        let old = dir.join(&tagged.path);
+        let new = old.with_file_name(&name);
+        if new.exists() || renames.iter().any(|(_, n)| *n == new) {
+            bail!(
+                "cannot normalize '{}': '{}' already exists",
+                tagged.path.display(),
+                tagged.path.with_file_name(&name).display()
+            );
+        }
+
+        writeln!(report, "-{}", tagged.path.display())?;
+        writeln!(report, "+{}", tagged.path.with_file_name(&name).display())?;
+        renames.push((old, new));
+    }
+
+    if args.apply {
+        rename::apply(&renames, Some(&args.journal))?;
+    } else if !renames.is_empty() {
+        writeln!(
+            report,
+            "dry run: {} entries would be renamed, use '--apply' to proceed",
+            renames.len()
+        )?;
+    }
+    if refused > 0 {
+        writeln!(report, "{} entries cannot be normalized", refused)?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::LazyLock;
+
+    use chrono::Locale;
+
+    use crate::{
+        calendar::Calendar,
+        datestyle::{DateStyle, StylePreset},
+        utils::ParseOptions,
+    };
+
+    static FINDER: LazyLock<Finder> = LazyLock::new(|| Finder::new(&ParseOptions::default()));
+
+    fn normalize(name: &str, tag_type: DateTag, preset: StylePreset) -> Result<String, String> {
+        let formatter = DateFormatter {
+            locale: Locale::POSIX,
+            calendar: Calendar::default(),
+        };
+        let tagged = Tagged::from_path(PathBuf::from(name), &FINDER).unwrap();
+
+        normalized(
+            &tagged,
+            tag_type,
+            &tag_type.get_format(&DateStyle::from(preset)),
+            &formatter,
+        )
+    }
+
+    #[test]
+    fn test_normalized() {
+        for name in [
+            "2024.04.03_x",
+            "2024-04-03_x",
+            "20240403_x",
+            "03-Apr-2024_x",
+        ] {
+            assert_eq!(
+                normalize(name, DateTag::Daily, StylePreset::Dash),
+                Ok("2024-04-03_x".to_string())
+            );
+        }
+        assert_eq!(
+            normalize("LAB_20240403.tar", DateTag::Monthly, StylePreset::Plain),
+            Ok("LAB_202404.tar".to_string())
+        );
+        assert_eq!(
+            normalize("2024-W17", DateTag::Weekly, StylePreset::IsoWeekCompact),
+            Ok("2024W17".to_string())
+        );
+    }
+
+    #[test]
+    fn test_normalized_refused() {
+        assert_eq!(
+            normalize("2024.04_x", DateTag::Daily, StylePreset::Dash),
+            Err("cannot convert monthly tags into daily tags".to_string())
+        );
+        assert_eq!(
+            normalize("2024-W18", DateTag::Monthly, StylePreset::Dash),
+            Err("cannot convert weekly tags into monthly tags".to_string())
+        );
+    }
+}
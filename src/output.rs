@@ -0,0 +1,245 @@
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// supported output formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// bare date tags
+    Text,
+    /// JSON array of tag records
+    Json,
+    /// one JSON tag record per line
+    Jsonl,
+    /// comma-separated tag records, with header
+    Csv,
+    /// tab-separated tag records, with header
+    Tsv,
+}
+
+/// date tag along with its metadata
+#[derive(Debug, Serialize)]
+pub struct TagRecord {
+    /// full date tag (e.g. 'LAB_202404_rel')
+    pub tag: String,
+    /// tag prefix label
+    pub prefix: String,
+    /// formatted date reference
+    pub date: String,
+    /// tag suffix label
+    pub suffix: String,
+    /// canonical tag type name
+    pub tag_type: &'static str,
+    /// date tag style, if not overridden by a custom format
+    pub style: Option<String>,
+    /// format string used for the date reference
+    pub format: String,
+    /// first day of the tagged period
+    pub period_start: NaiveDate,
+    /// last day of the tagged period
+    pub period_end: NaiveDate,
+    /// position of the tag in the generated sequence (starting from 0)
+    pub index: usize,
+}
+
+/// header of CSV and TSV output, matching the fields of tag records
+const HEADER: [&str; 10] = [
+    "tag",
+    "prefix",
+    "date",
+    "suffix",
+    "tag_type",
+    "style",
+    "format",
+    "period_start",
+    "period_end",
+    "index",
+];
+
+/// writer of date tags in the selected output format
+pub struct TagWriter<W: Write> {
+    sink: Sink<W>,
+    new_line: bool,
+    count: usize,
+}
+
+enum Sink<W: Write> {
+    Text(W),
+    Json(W),
+    Jsonl(W),
+    Csv(Box<csv::Writer<W>>),
+}
+
+impl<W: Write> TagWriter<W> {
+    /// create a new writer; `new_line` only affects text output
+    ///
+    /// The header of CSV and TSV output is written right away, so that
+    /// empty output still has one.
+    pub fn new(output: W, format: OutputFormat, new_line: bool) -> Result<Self> {
+        let sink = match format {
+            OutputFormat::Text => Sink::Text(output),
+            OutputFormat::Json => Sink::Json(output),
+            OutputFormat::Jsonl => Sink::Jsonl(output),
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let mut w = csv::WriterBuilder::new()
+                    .delimiter(if format == OutputFormat::Tsv {
+                        b'\t'
+                    } else {
+                        b','
+                    })
+                    .has_headers(false)
+                    .from_writer(output);
+                w.write_record(HEADER)?;
+                Sink::Csv(Box::new(w))
+            }
+        };
+
+        Ok(TagWriter {
+            sink,
+            new_line,
+            count: 0,
+        })
+    }
+
+    /// true if records are written as plain text
+    pub fn is_text(&self) -> bool {
+        matches!(self.sink, Sink::Text(_))
+    }
+
+    /// write a single tag record
+    pub fn write(&mut self, record: &TagRecord) -> Result<()> {
+        match &mut self.sink {
+            Sink::Text(w) => {
+                write!(w, "{}", record.tag)?;
+                if self.new_line {
+                    writeln!(w)?;
+                }
+            }
+            Sink::Json(w) => {
+                w.write_all(if self.count == 0 { b"[\n" } else { b",\n" })?;
+                serde_json::to_writer(&mut *w, record)?;
+            }
+            Sink::Jsonl(w) => {
+                serde_json::to_writer(&mut *w, record)?;
+                writeln!(w)?;
+            }
+            Sink::Csv(w) => w.serialize(record)?,
+        }
+
+        self.count += 1;
+        Ok(())
+    }
+
+    /// write an unconverted entry, as is (plain text output only)
+    pub fn write_raw(&mut self, raw: &str) -> Result<()> {
+        if let Sink::Text(w) = &mut self.sink {
+            writeln!(w, "{}", raw)?;
+        }
+
+        Ok(())
+    }
+
+    /// complete the output and flush any buffered data
+    pub fn finish(self) -> Result<()> {
+        match self.sink {
+            Sink::Text(mut w) | Sink::Jsonl(mut w) => w.flush()?,
+            Sink::Json(mut w) => {
+                w.write_all(if self.count == 0 { b"[]\n" } else { b"\n]\n" })?;
+                w.flush()?;
+            }
+            Sink::Csv(mut w) => w.flush()?,
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(index: usize, day: u32) -> TagRecord {
+        let date = NaiveDate::from_ymd_opt(2024, 4, day).unwrap();
+        TagRecord {
+            tag: format!("LAB_{}", date.format("%Y%m%d")),
+            prefix: String::from("LAB_"),
+            date: date.format("%Y%m%d").to_string(),
+            suffix: String::new(),
+            tag_type: "daily",
+            style: Some(String::from("plain")),
+            format: String::from("%Y%m%d"),
+            period_start: date,
+            period_end: date,
+            index,
+        }
+    }
+
+    fn write_all(format: OutputFormat, new_line: bool, records: &[TagRecord]) -> String {
+        let mut output = Vec::new();
+        let mut writer = TagWriter::new(&mut output, format, new_line).unwrap();
+        for r in records {
+            writer.write(r).unwrap();
+        }
+        writer.finish().unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_write_text() {
+        let s = write_all(OutputFormat::Text, false, &[record(0, 27)]);
+        assert_eq!(s, "LAB_20240427");
+        let s = write_all(OutputFormat::Text, true, &[record(0, 27), record(1, 28)]);
+        assert_eq!(s, "LAB_20240427\nLAB_20240428\n");
+    }
+
+    #[test]
+    fn test_write_json() {
+        let s = write_all(OutputFormat::Json, false, &[record(0, 27), record(1, 28)]);
+        let v: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(v[1]["tag"], "LAB_20240428");
+        assert_eq!(v[1]["period_start"], "2024-04-28");
+        assert_eq!(v[1]["index"], 1);
+    }
+
+    #[test]
+    fn test_write_json_empty() {
+        let s = write_all(OutputFormat::Json, false, &[]);
+        assert_eq!(s, "[]\n");
+    }
+
+    #[test]
+    fn test_write_jsonl() {
+        let s = write_all(OutputFormat::Jsonl, false, &[record(0, 27), record(1, 28)]);
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("{\"tag\":\"LAB_20240427\""));
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let s = write_all(OutputFormat::Csv, false, &[record(0, 27)]);
+        assert_eq!(
+            s,
+            "tag,prefix,date,suffix,tag_type,style,format,period_start,period_end,index\n\
+             LAB_20240427,LAB_,20240427,,daily,plain,%Y%m%d,2024-04-27,2024-04-27,0\n"
+        );
+    }
+
+    #[test]
+    fn test_write_csv_empty() {
+        let s = write_all(OutputFormat::Csv, false, &[]);
+        assert_eq!(
+            s,
+            "tag,prefix,date,suffix,tag_type,style,format,period_start,period_end,index\n"
+        );
+    }
+
+    #[test]
+    fn test_write_tsv() {
+        let s = write_all(OutputFormat::Tsv, false, &[record(0, 27)]);
+        assert!(s.starts_with("tag\tprefix\tdate\t"));
+    }
+}
@@ -0,0 +1,354 @@
+//! Retention of date tagged entries, following grandfather-father-son
+//! policies (e.g. 7 daily, 4 weekly and 12 monthly backups)
+//!
+//! Entries are considered from the newest to the oldest one: each rule
+//! keeps the newest entry of its most recent periods (e.g. the last 4
+//! weeks holding an entry), the others are pruned. Entries only fill
+//! the rules whose periods are at least as long as the one of their tag
+//! (e.g. a monthly tag never counts as a daily entry), and directories
+//! are only considered along with a prefix.
+
+use std::{
+    fmt::Write,
+    fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+
+use crate::{
+    calendar::Calendar,
+    datetag::DateTag,
+    finder::Finder,
+    scan::{self, Tagged},
+};
+
+/// arguments of the prune subcommand
+#[derive(Debug, clap::Args)]
+pub struct PruneArgs {
+    /// Directory of the entries, using the current directory if not specified
+    #[arg(conflicts_with = "stdin")]
+    dir: Option<PathBuf>,
+
+    /// Read entry paths from the standard input, one per line
+    #[arg(long)]
+    stdin: bool,
+
+    /// Number of days whose newest entry is kept
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    keep_daily: usize,
+
+    /// Number of weeks whose newest entry is kept
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    keep_weekly: usize,
+
+    /// Number of months whose newest entry is kept
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    keep_monthly: usize,
+
+    /// Number of quarters whose newest entry is kept
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    keep_quarterly: usize,
+
+    /// Number of years whose newest entry is kept
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    keep_yearly: usize,
+
+    /// Only consider entries whose name starts with the given label,
+    /// followed by the tag, directories being ignored without it
+    #[arg(long)]
+    prefix: Option<String>,
+
+    /// Action applied to pruned entries
+    #[arg(value_enum, long, default_value_t = Action::Print)]
+    action: Action,
+
+    /// Destination directory of moved entries
+    #[arg(long, value_name = "DIR", required_if_eq("action", "move"))]
+    to: Option<PathBuf>,
+
+    /// Delete or move pruned entries, instead of reporting what would be done
+    #[arg(long)]
+    apply: bool,
+}
+
+/// actions applied to pruned entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Action {
+    /// print the paths of pruned entries only, one per line
+    Print,
+    /// delete pruned entries
+    Delete,
+    /// move pruned entries to the '--to' directory
+    Move,
+}
+
+/// entry along with the rules keeping it, if any
+struct Decision {
+    tagged: Tagged,
+    kept_by: Vec<&'static str>,
+}
+
+/// rank of the period length of a tag type, from days to years
+fn rank(tag_type: &DateTag) -> u8 {
+    match tag_type {
+        DateTag::D
+        | DateTag::Daily
+        | DateTag::Wd
+        | DateTag::WeekDay
+        | DateTag::O
+        | DateTag::Ordinal
+        | DateTag::Ed
+        | DateTag::EpochDay
+        | DateTag::Es
+        | DateTag::EpochSeconds
+        | DateTag::B36
+        | DateTag::Base36
+        | DateTag::X
+        | DateTag::Hex => 0,
+        DateTag::W | DateTag::Weekly | DateTag::Mw | DateTag::WeekOfMonth => 1,
+        DateTag::M | DateTag::Monthly => 2,
+        DateTag::Q | DateTag::Quarterly => 3,
+        DateTag::Y | DateTag::Yearly => 4,
+    }
+}
+
+/// keep the newest entry of the most recent periods of each rule
+///
+/// Entries are sorted from the newest to the oldest one.
+fn decide(
+    mut entries: Vec<Tagged>,
+    rules: &[(DateTag, usize)],
+    calendar: &Calendar,
+) -> Vec<Decision> {
+    entries.sort_by(|a, b| (b.found.date, &b.name).cmp(&(a.found.date, &a.name)));
+
+    let mut decisions: Vec<Decision> = entries
+        .into_iter()
+        .map(|tagged| Decision {
+            tagged,
+            kept_by: Vec::new(),
+        })
+        .collect();
+
+    for (tag_type, count) in rules {
+        let mut periods: Vec<NaiveDate> = Vec::new();
+        for decision in &mut decisions {
+            if periods.len() == *count {
+                break;
+            }
+            if rank(&decision.tagged.found.tag_type) > rank(tag_type) {
+                continue;
+            }
+
+            let Some((start, _)) = tag_type.period(&decision.tagged.found.date, calendar) else {
+                continue;
+            };
+            if !periods.contains(&start) {
+                periods.push(start);
+                decision.kept_by.push(tag_type.name());
+            }
+        }
+    }
+
+    decisions
+}
+
+/// entries to consider, read from a directory or the standard input
+fn entries(args: &PruneArgs, finder: &Finder) -> Result<Vec<Tagged>> {
+    let entries = if args.stdin {
+        let mut entries = Vec::new();
+        for line in io::stdin().lock().lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                entries.extend(Tagged::from_path(PathBuf::from(line.trim()), finder));
+            }
+        }
+        entries
+    } else {
+        scan::entries(&base(args), Some(0), finder)?
+    };
+
+    // directories are only pruned when selected by a prefix
+    let is_dir =
+        |t: &Tagged| fs::symlink_metadata(base(args).join(&t.path)).is_ok_and(|m| m.is_dir());
+
+    Ok(entries
+        .into_iter()
+        .filter(|t| args.prefix.as_ref().is_none_or(|p| t.prefix() == p))
+        .filter(|t| args.prefix.is_some() || !is_dir(t))
+        .collect())
+}
+
+/// directory of the entries, paths read from the standard input being
+/// relative to the current directory
+fn base(args: &PruneArgs) -> PathBuf {
+    match (&args.dir, args.stdin) {
+        (Some(dir), false) => dir.clone(),
+        (None, false) => PathBuf::from("."),
+        (_, true) => PathBuf::new(),
+    }
+}
+
+/// delete or move a pruned entry
+fn apply(path: &Path, name: &str, action: Action, to: Option<&Path>) -> Result<()> {
+    match (action, to) {
+        (Action::Delete, _) => {
+            if fs::symlink_metadata(path)?.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            }
+            .with_context(|| format!("cannot delete '{}'", path.display()))?;
+        }
+        (Action::Move, Some(to)) => {
+            let target = to.join(name);
+            if target.exists() {
+                bail!(
+                    "cannot move '{}': '{}' already exists",
+                    path.display(),
+                    target.display()
+                );
+            }
+            fs::create_dir_all(to)
+                .with_context(|| format!("cannot create directory '{}'", to.display()))?;
+            fs::rename(path, &target)
+                .with_context(|| format!("cannot move '{}'", path.display()))?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// prune the entries not kept by any rule
+pub fn run(args: &PruneArgs, finder: &Finder, calendar: &Calendar) -> Result<String> {
+    let rules: Vec<(DateTag, usize)> = [
+        (DateTag::Daily, args.keep_daily),
+        (DateTag::Weekly, args.keep_weekly),
+        (DateTag::Monthly, args.keep_monthly),
+        (DateTag::Quarterly, args.keep_quarterly),
+        (DateTag::Yearly, args.keep_yearly),
+    ]
+    .into_iter()
+    .filter(|(_, count)| *count > 0)
+    .collect();
+    if rules.is_empty() {
+        bail!("at least one '--keep-*' rule is required");
+    }
+
+    let base = base(args);
+    let decisions = decide(entries(args, finder)?, &rules, calendar);
+
+    let mut report = String::new();
+    let mut pruned = 0;
+    for decision in &decisions {
+        let path = decision.tagged.path.display();
+
+        match (args.action, decision.kept_by.is_empty()) {
+            (Action::Print, true) => writeln!(report, "{}", path)?,
+            (Action::Print, false) => {}
+            (_, false) => writeln!(report, "keep    {} ({})", path, decision.kept_by.join(", "))?,
+            (action, true) => {
+                if args.apply {
+                    let full = base.join(&decision.tagged.path);
+                    apply(&full, &decision.tagged.name, action, args.to.as_deref())?;
+                }
+                let verb = if action == Action::Delete {
+                    "delete"
+                } else {
+                    "move"
+                };
+                writeln!(report, "{:8}{}", verb, path)?;
+            }
+        }
+
+        if decision.kept_by.is_empty() {
+            pruned += 1;
+        }
+    }
+
+    if args.action != Action::Print && !args.apply && pruned > 0 {
+        writeln!(
+            report,
+            "dry run: {} entries would be pruned, use '--apply' to proceed",
+            pruned
+        )?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::utils::ParseOptions;
+
+    fn kept(names: &[&str], rules: &[(DateTag, usize)]) -> Vec<String> {
+        let finder = Finder::new(&ParseOptions::default());
+        let entries = names
+            .iter()
+            .filter_map(|n| Tagged::from_path(PathBuf::from(n), &finder))
+            .collect();
+
+        decide(entries, rules, &Calendar::default())
+            .into_iter()
+            .filter(|d| !d.kept_by.is_empty())
+            .map(|d| d.tagged.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_decide() {
+        let names = [
+            "backup_20240427.tar",
+            "backup_20240426.tar",
+            "backup_20240420.tar",
+            "backup_20240331.tar",
+            "backup_20240315.tar",
+            "backup_20231231.tar",
+        ];
+        assert_eq!(
+            kept(&names, &[(DateTag::Daily, 2)]),
+            vec!["backup_20240427.tar", "backup_20240426.tar"]
+        );
+        assert_eq!(
+            kept(&names, &[(DateTag::Weekly, 2)]),
+            vec!["backup_20240427.tar", "backup_20240420.tar"]
+        );
+        assert_eq!(
+            kept(&names, &[(DateTag::Daily, 1), (DateTag::Monthly, 3)]),
+            vec![
+                "backup_20240427.tar",
+                "backup_20240331.tar",
+                "backup_20231231.tar"
+            ]
+        );
+        assert_eq!(
+            kept(&names, &[(DateTag::Yearly, 5)]),
+            vec!["backup_20240427.tar", "backup_20231231.tar"]
+        );
+    }
+
+    #[test]
+    fn test_decide_coarser_tags() {
+        let names = [
+            "backup_2025.tar",
+            "backup_2025-02.tar",
+            "backup_20250103.tar",
+            "backup_20250102.tar",
+        ];
+        assert_eq!(
+            kept(&names, &[(DateTag::Daily, 2)]),
+            vec!["backup_20250103.tar", "backup_20250102.tar"]
+        );
+        assert_eq!(
+            kept(&names, &[(DateTag::Monthly, 2)]),
+            vec!["backup_2025-02.tar", "backup_20250103.tar"]
+        );
+    }
+}
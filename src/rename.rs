@@ -0,0 +1,434 @@
+//! Renaming of files, adding or replacing a date tag in their names
+//! (e.g. 'IMG_0042.jpg' into '20240427_IMG_0042.jpg')
+//!
+//! Dates come from the file modification time, from a date embedded in the
+//! file content (EXIF and TIFF timestamps, PDF creation dates) or from a
+//! fixed reference date. Applied renames are recorded in a journal, which
+//! allows to undo the last run.
+
+use std::{
+    collections::HashSet,
+    fmt::Write as _,
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use regex::bytes::Regex;
+
+use crate::finder::Finder;
+
+/// default journal of applied renames
+pub const DEFAULT_JOURNAL: &str = ".datetag-journal";
+
+/// bytes of the file content searched for an embedded date
+const EMBEDDED_DATE_BYTES: u64 = 64 * 1024;
+
+/// matches EXIF and TIFF timestamps (e.g. '2024:04:27 10:42:00') or PDF
+/// dates (e.g. 'D:20240427104200')
+static EMBEDDED_DATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\d{4}):(\d{2}):(\d{2}) \d{2}:\d{2}:\d{2}|D:(\d{4})(\d{2})(\d{2})").unwrap()
+});
+
+/// arguments of the rename subcommand
+#[derive(Debug, clap::Args)]
+pub struct RenameArgs {
+    /// Files to rename
+    #[arg(required_unless_present = "undo")]
+    files: Vec<PathBuf>,
+
+    /// Position of the tag in the new names
+    #[arg(value_enum, long, default_value_t = Position::Prefix)]
+    position: Position,
+
+    /// Origin of the date of each file
+    #[arg(value_enum, long, default_value_t = Source::Mtime)]
+    source: Source,
+
+    /// Use the given reference date for all the files, instead of --source
+    #[arg(long, value_name = "DATE", conflicts_with = "source")]
+    date: Option<String>,
+
+    /// Separator between the tag and the rest of the name
+    #[arg(long, default_value = "_")]
+    join: String,
+
+    /// Policy applied when a new name is already taken
+    #[arg(value_enum, long, default_value_t = Conflict::Fail)]
+    on_conflict: Conflict,
+
+    /// Journal of applied renames, used by --undo
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_JOURNAL)]
+    journal: PathBuf,
+
+    /// Revert the renames recorded in the journal
+    #[arg(long, conflicts_with_all = ["position", "source", "date"])]
+    undo: bool,
+
+    /// Rename files, instead of reporting what would be done
+    #[arg(long)]
+    apply: bool,
+}
+
+/// positions of the tag in new names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Position {
+    /// before the name (e.g. '20240427_report.pdf')
+    Prefix,
+    /// after the name, before its extensions (e.g. 'report_20240427.pdf')
+    Suffix,
+    /// in place of the first tag of the name, other files being skipped
+    Replace,
+}
+
+/// origins of the date of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Source {
+    /// file modification time
+    Mtime,
+    /// date embedded in the file content (EXIF, TIFF or PDF)
+    Metadata,
+    /// date tag of the file name
+    Name,
+}
+
+impl Source {
+    /// reason why a file has no date
+    fn missing(&self) -> &'static str {
+        match self {
+            Source::Mtime => "no modification time",
+            Source::Metadata => "no embedded date",
+            Source::Name => "no date tag",
+        }
+    }
+}
+
+/// policies applied when a new name is already taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Conflict {
+    /// stop before renaming any file
+    Fail,
+    /// leave the file unchanged
+    Skip,
+    /// append a number to the new name (e.g. '20240427_report_2.pdf')
+    Number,
+}
+
+/// planned change of a file
+enum Change {
+    Rename(PathBuf, PathBuf),
+    Skip(PathBuf, &'static str),
+}
+
+/// split a name into its stem and extensions (e.g. 'backup' and '.tar.gz'),
+/// a leading dot being part of the stem
+fn split_extension(name: &str) -> (&str, &str) {
+    match name.char_indices().skip(1).find(|(_, c)| *c == '.') {
+        Some((index, _)) => name.split_at(index),
+        None => (name, ""),
+    }
+}
+
+/// date embedded in the first bytes of a file, if any
+fn embedded_date(path: &Path) -> Result<Option<NaiveDate>> {
+    let mut content = Vec::new();
+    File::open(path)
+        .with_context(|| format!("cannot read '{}'", path.display()))?
+        .take(EMBEDDED_DATE_BYTES)
+        .read_to_end(&mut content)?;
+
+    Ok(EMBEDDED_DATE.captures_iter(&content).find_map(|caps| {
+        let field = |i: usize, j: usize| -> Option<u32> {
+            let m = caps.get(i).or_else(|| caps.get(j))?;
+            std::str::from_utf8(m.as_bytes()).ok()?.parse().ok()
+        };
+        NaiveDate::from_ymd_opt(field(1, 4)? as i32, field(2, 5)?, field(3, 6)?)
+    }))
+}
+
+/// date of a file, according to the selected source
+pub fn file_date(path: &Path, source: Source, finder: &Finder) -> Result<Option<NaiveDate>> {
+    match source {
+        Source::Mtime => {
+            let modified = fs::metadata(path)
+                .with_context(|| format!("cannot read '{}'", path.display()))?
+                .modified()?;
+            Ok(Some(DateTime::<Utc>::from(modified).date_naive()))
+        }
+        Source::Metadata => embedded_date(path),
+        Source::Name => Ok(path
+            .file_name()
+            .and_then(|name| finder.find(&name.to_string_lossy()))
+            .map(|found| found.date)),
+    }
+}
+
+/// new name of a file, if it can be tagged
+fn new_name(
+    name: &str,
+    tag: &str,
+    args: &RenameArgs,
+    finder: &Finder,
+) -> Result<String, &'static str> {
+    let found = finder.find(name);
+
+    match (args.position, found) {
+        (Position::Replace, Some(found)) => Ok(format!(
+            "{}{}{}",
+            &name[..found.start],
+            tag,
+            &name[found.end..]
+        )),
+        (Position::Replace, None) => Err("no tag to replace"),
+        (_, Some(_)) => Err("already tagged"),
+        (Position::Prefix, None) => Ok(format!("{}{}{}", tag, args.join, name)),
+        (Position::Suffix, None) => {
+            let (stem, extension) = split_extension(name);
+            Ok(format!("{}{}{}{}", stem, args.join, tag, extension))
+        }
+    }
+}
+
+/// first free variant of a path, numbered before its extensions
+pub fn numbered(path: &Path, taken: &HashSet<PathBuf>) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let (stem, extension) = split_extension(&name);
+
+    (2..)
+        .map(|n| path.with_file_name(format!("{}_{}{}", stem, n, extension)))
+        .find(|p| !p.exists() && !taken.contains(p))
+        .unwrap()
+}
+
+/// plan the renames of the files
+fn plan(
+    args: &RenameArgs,
+    finder: &Finder,
+    date: Option<NaiveDate>,
+    reference: impl Fn(&NaiveDate) -> String,
+) -> Result<Vec<Change>> {
+    let mut changes = Vec::new();
+    let mut taken: HashSet<PathBuf> = HashSet::new();
+
+    for path in &args.files {
+        let date = match date {
+            Some(date) => Some(date),
+            None => file_date(path, args.source, finder)?,
+        };
+        let Some(date) = date else {
+            changes.push(Change::Skip(path.clone(), args.source.missing()));
+            continue;
+        };
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut target = match new_name(&name, &reference(&date), args, finder) {
+            Ok(name) => path.with_file_name(name),
+            Err(reason) => {
+                changes.push(Change::Skip(path.clone(), reason));
+                continue;
+            }
+        };
+
+        if target == *path {
+            changes.push(Change::Skip(path.clone(), "already up to date"));
+            continue;
+        }
+
+        if target.exists() || taken.contains(&target) {
+            match args.on_conflict {
+                Conflict::Fail => bail!(
+                    "cannot rename '{}': '{}' already exists",
+                    path.display(),
+                    target.display()
+                ),
+                Conflict::Skip => {
+                    changes.push(Change::Skip(path.clone(), "new name already taken"));
+                    continue;
+                }
+                Conflict::Number => target = numbered(&target, &taken),
+            }
+        }
+
+        taken.insert(target.clone());
+        changes.push(Change::Rename(path.clone(), target));
+    }
+
+    Ok(changes)
+}
+
+/// renames recorded in a journal, made of 'old<TAB>new' lines
+fn read_journal(path: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    fs::read_to_string(path)
+        .with_context(|| format!("cannot read journal '{}'", path.display()))?
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (old, new) = line
+                .split_once('\t')
+                .with_context(|| format!("wrong journal line '{}'", line))?;
+            Ok((PathBuf::from(old), PathBuf::from(new)))
+        })
+        .collect()
+}
+
+/// rename files, recording each rename in the journal
+///
+/// The journal of the previous run is kept if there is nothing to rename.
+pub fn apply(renames: &[(PathBuf, PathBuf)], journal: Option<&Path>) -> Result<()> {
+    let mut journal = journal
+        .filter(|_| !renames.is_empty())
+        .map(|path| {
+            File::create(path).with_context(|| format!("cannot write journal '{}'", path.display()))
+        })
+        .transpose()?;
+
+    for (old, new) in renames {
+        if new.exists() {
+            bail!(
+                "cannot rename '{}': '{}' already exists",
+                old.display(),
+                new.display()
+            );
+        }
+        fs::rename(old, new).with_context(|| format!("cannot rename '{}'", old.display()))?;
+
+        if let Some(journal) = journal.as_mut() {
+            writeln!(
+                journal,
+                "{}\t{}",
+                std::path::absolute(old)?.display(),
+                std::path::absolute(new)?.display()
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// add or replace a date tag in the names of the files, or undo the last run
+pub fn run(
+    args: &RenameArgs,
+    finder: &Finder,
+    parse: impl Fn(&str) -> Result<NaiveDate>,
+    reference: impl Fn(&NaiveDate) -> String,
+) -> Result<String> {
+    let mut report = String::new();
+
+    let renames: Vec<(PathBuf, PathBuf)> = if args.undo {
+        // files restored by an interrupted undo are left as they are
+        let mut renames = Vec::new();
+        for (old, new) in read_journal(&args.journal)?.into_iter().rev() {
+            if !new.exists() && old.exists() {
+                writeln!(report, "skip    {} (already restored)", old.display())?;
+            } else {
+                renames.push((new, old));
+            }
+        }
+        renames
+    } else {
+        let date = args.date.as_deref().map(parse).transpose()?;
+        let mut renames = Vec::new();
+        for change in plan(args, finder, date, reference)? {
+            match change {
+                Change::Rename(old, new) => renames.push((old, new)),
+                Change::Skip(path, reason) => {
+                    writeln!(report, "skip    {} ({})", path.display(), reason)?
+                }
+            }
+        }
+        renames
+    };
+
+    for (old, new) in &renames {
+        writeln!(report, "rename  {} -> {}", old.display(), new.display())?;
+    }
+
+    if args.apply {
+        apply(&renames, (!args.undo).then_some(args.journal.as_path()))?;
+        if args.undo {
+            fs::remove_file(&args.journal)?;
+        }
+    } else if !renames.is_empty() {
+        writeln!(
+            report,
+            "dry run: {} files would be renamed, use '--apply' to proceed",
+            renames.len()
+        )?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use clap::Parser;
+
+    use crate::utils::ParseOptions;
+
+    static FINDER: LazyLock<Finder> = LazyLock::new(|| Finder::new(&ParseOptions::default()));
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: RenameArgs,
+    }
+
+    fn name(name: &str, options: &str) -> Result<String, &'static str> {
+        let cli = Cli::parse_from(format!("rename {} {}", name, options).split_whitespace());
+        new_name(name, "20240427", &cli.args, &FINDER)
+    }
+
+    #[test]
+    fn test_split_extension() {
+        assert_eq!(split_extension("backup.tar.gz"), ("backup", ".tar.gz"));
+        assert_eq!(split_extension("README"), ("README", ""));
+        assert_eq!(split_extension(".profile"), (".profile", ""));
+    }
+
+    #[test]
+    fn test_new_name() {
+        assert_eq!(
+            name("report.pdf", ""),
+            Ok("20240427_report.pdf".to_string())
+        );
+        assert_eq!(
+            name("backup.tar.gz", "--position suffix --join -"),
+            Ok("backup-20240427.tar.gz".to_string())
+        );
+        assert_eq!(
+            name("log_2024-04-03.txt", "--position replace"),
+            Ok("log_20240427.txt".to_string())
+        );
+        assert_eq!(name("log_2024-04-03.txt", ""), Err("already tagged"));
+        assert_eq!(
+            name("notes.txt", "--position replace"),
+            Err("no tag to replace")
+        );
+    }
+
+    #[test]
+    fn test_embedded_date() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.join("photo");
+        fs::write(&path, b"\xff\xd8Exif\0\0MM2024:04:27 10:42:00\0").unwrap();
+        assert_eq!(
+            embedded_date(&path).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 4, 27)
+        );
+
+        fs::write(&path, b"%PDF-1.7 /CreationDate (D:20230105120000Z)").unwrap();
+        assert_eq!(
+            embedded_date(&path).unwrap(),
+            NaiveDate::from_ymd_opt(2023, 1, 5)
+        );
+
+        fs::write(&path, b"no date").unwrap();
+        assert_eq!(embedded_date(&path).unwrap(), None);
+    }
+}
@@ -0,0 +1,402 @@
+use chrono::{Datelike, Months, NaiveDate, TimeDelta};
+use clap::ValueEnum;
+
+use crate::datetag::DateTag;
+
+/// number of weeks of the periods of each quarter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RetailPattern {
+    /// 4-4-5 weeks
+    #[value(name = "445")]
+    P445,
+    /// 4-5-4 weeks
+    #[value(name = "454")]
+    P454,
+    /// 5-4-4 weeks
+    #[value(name = "544")]
+    P544,
+}
+
+impl RetailPattern {
+    fn weeks(&self) -> [i64; 3] {
+        match self {
+            RetailPattern::P445 => [4, 4, 5],
+            RetailPattern::P454 => [4, 5, 4],
+            RetailPattern::P544 => [5, 4, 4],
+        }
+    }
+}
+
+/// rule used to find the last day of a fiscal year
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum YearEnd {
+    /// last Saturday of the year-end month
+    Last,
+    /// Saturday nearest to the end of the year-end month
+    Nearest,
+}
+
+/// calendar year fiscal years are named after
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum YearName {
+    /// year before the one the fiscal year ends in (e.g. NRF fiscal 2024,
+    /// ending in early 2025)
+    Start,
+    /// year the fiscal year ends in
+    End,
+}
+
+/// retail fiscal calendar, made of 52 or 53 weeks from Sunday to Saturday
+///
+/// Fiscal years are named after the calendar year they start or end in,
+/// the extra week of 53-week years belongs to the last period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetailCalendar {
+    pub pattern: RetailPattern,
+    pub year_end: YearEnd,
+    /// month of the last day of fiscal years (1-12)
+    pub end_month: u32,
+    pub year_name: YearName,
+}
+
+impl Default for RetailCalendar {
+    /// NRF calendar: 4-5-4 weeks, ending on the Saturday nearest to January 31st,
+    /// years being named after the calendar year they start in
+    fn default() -> Self {
+        RetailCalendar {
+            pattern: RetailPattern::P454,
+            year_end: YearEnd::Nearest,
+            end_month: 1,
+            year_name: YearName::Start,
+        }
+    }
+}
+
+/// fields of a date in a retail calendar, all starting from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetailDate {
+    pub year: i32,
+    pub quarter: u32,
+    pub period: u32,
+    pub week: u32,
+    pub week_of_period: u32,
+    pub weekday: u32,
+    pub day_of_period: u32,
+    pub day_of_year: u32,
+}
+
+impl RetailCalendar {
+    /// name of the fiscal year ending in the given calendar year
+    fn name(&self, year: i32) -> i32 {
+        match self.year_name {
+            YearName::Start => year - 1,
+            YearName::End => year,
+        }
+    }
+
+    /// calendar year the fiscal year of the given name ends in
+    fn end_year(&self, name: i32) -> i32 {
+        match self.year_name {
+            YearName::Start => name + 1,
+            YearName::End => name,
+        }
+    }
+
+    /// last day of the fiscal year ending in the given calendar year
+    fn last_day(&self, year: i32) -> Option<NaiveDate> {
+        let end = NaiveDate::from_ymd_opt(year, self.end_month, 1)?
+            .checked_add_months(Months::new(1))?
+            .pred_opt()?;
+
+        // days since the previous Saturday
+        let back = (end.weekday().num_days_from_sunday() + 1) % 7;
+        let days = match self.year_end {
+            YearEnd::Nearest if back > 3 => 7 - back as i64,
+            _ => -(back as i64),
+        };
+
+        end.checked_add_signed(TimeDelta::days(days))
+    }
+
+    /// fiscal year containing the given date, with its first and last day
+    fn year(&self, date: &NaiveDate) -> Option<(i32, NaiveDate, NaiveDate)> {
+        let mut year = date.year();
+        if *date > self.last_day(year)? {
+            year += 1;
+        } else if *date <= self.last_day(year - 1)? {
+            year -= 1;
+        }
+
+        Some((
+            year,
+            self.last_day(year - 1)?.succ_opt()?,
+            self.last_day(year)?,
+        ))
+    }
+
+    /// first and last day of a period (1-12) of the given fiscal year
+    fn period_bounds(&self, year: i32, period: u32) -> Option<(NaiveDate, NaiveDate)> {
+        let start = self.last_day(year - 1)?.succ_opt()?;
+        let end = self.last_day(year)?;
+
+        let weeks = self.pattern.weeks();
+        let before: i64 = (0..period as usize - 1).map(|p| weeks[p % 3]).sum();
+        let first = start.checked_add_signed(TimeDelta::weeks(before))?;
+
+        // the last period ends with the fiscal year, including any extra week
+        let last = match period {
+            12 => end,
+            _ => first
+                .checked_add_signed(TimeDelta::weeks(weeks[(period as usize - 1) % 3]))?
+                .pred_opt()?,
+        };
+
+        Some((first, last))
+    }
+
+    /// fields of the given date
+    pub fn date(&self, date: &NaiveDate) -> Option<RetailDate> {
+        let (year, start, _) = self.year(date)?;
+        let day0 = (*date - start).num_days() as u32;
+
+        let period = (1..=12)
+            .find(|p| {
+                self.period_bounds(year, *p)
+                    .is_some_and(|(_, last)| *date <= last)
+            })
+            .unwrap_or(12);
+        let (first, _) = self.period_bounds(year, period)?;
+        let period_day0 = (*date - first).num_days() as u32;
+
+        Some(RetailDate {
+            year: self.name(year),
+            quarter: (period - 1) / 3 + 1,
+            period,
+            week: day0 / 7 + 1,
+            week_of_period: period_day0 / 7 + 1,
+            weekday: day0 % 7 + 1,
+            day_of_period: period_day0 + 1,
+            day_of_year: day0 + 1,
+        })
+    }
+
+    /// value of a specifier, given its padding modifier, replacing gregorian
+    /// years, quarters, months (periods) and weeks with fiscal ones
+    pub fn specifier(&self, date: &NaiveDate, pad: Option<char>, spec: char) -> Option<String> {
+        let d = self.date(date)?;
+
+        let (value, width) = match spec {
+            'Y' | 'G' => (d.year as u32, 4),
+            'y' | 'g' => (d.year.rem_euclid(100) as u32, 2),
+            'C' => (d.year.div_euclid(100) as u32, 2),
+            'q' => (d.quarter, 1),
+            'm' => (d.period, 2),
+            'V' => (d.week, 2),
+            'K' => (d.week_of_period, 1),
+            'u' => (d.weekday, 1),
+            'd' => (d.day_of_period, 2),
+            'j' => (d.day_of_year, 3),
+            _ => return None,
+        };
+
+        Some(match pad {
+            Some('-') => value.to_string(),
+            Some('_') => format!("{:w$}", value, w = width),
+            _ => format!("{:0w$}", value, w = width),
+        })
+    }
+
+    /// first and last day of the fiscal period of a date tag type
+    pub fn period(&self, tag_type: &DateTag, date: &NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        let d = self.date(date)?;
+        let year = self.end_year(d.year);
+
+        match tag_type {
+            DateTag::Y | DateTag::Yearly => self.year(date).map(|(_, first, last)| (first, last)),
+            DateTag::Q | DateTag::Quarterly => {
+                let first = self.period_bounds(year, d.quarter * 3 - 2)?.0;
+                let last = self.period_bounds(year, d.quarter * 3)?.1;
+                Some((first, last))
+            }
+            DateTag::M | DateTag::Monthly => self.period_bounds(year, d.period),
+            DateTag::W | DateTag::Weekly | DateTag::Mw | DateTag::WeekOfMonth => {
+                let first = date.checked_sub_signed(TimeDelta::days(d.weekday as i64 - 1))?;
+                Some((first, first.checked_add_signed(TimeDelta::days(6))?))
+            }
+            DateTag::D
+            | DateTag::Daily
+            | DateTag::Wd
+            | DateTag::WeekDay
+            | DateTag::O
+            | DateTag::Ordinal
+            | DateTag::Ed
+            | DateTag::EpochDay
+            | DateTag::Es
+            | DateTag::EpochSeconds
+            | DateTag::B36
+            | DateTag::Base36
+            | DateTag::X
+            | DateTag::Hex => Some((*date, *date)),
+        }
+    }
+
+    /// move a date by the given number of fiscal periods, keeping its
+    /// position within the period when possible
+    pub fn checked_add_periods(&self, date: &NaiveDate, periods: i32) -> Option<NaiveDate> {
+        let d = self.date(date)?;
+
+        let index = self.end_year(d.year) as i64 * 12 + d.period as i64 - 1 + periods as i64;
+        let year = i32::try_from(index.div_euclid(12)).ok()?;
+        let (first, last) = self.period_bounds(year, index.rem_euclid(12) as u32 + 1)?;
+
+        let target = first.checked_add_signed(TimeDelta::days(d.day_of_period as i64 - 1))?;
+        Some(target.min(last))
+    }
+
+    /// apply an offset, expressed in units of the date tag type
+    pub fn checked_add_offset(
+        &self,
+        date: &NaiveDate,
+        offset: i32,
+        tag_type: &DateTag,
+    ) -> Option<NaiveDate> {
+        match tag_type {
+            DateTag::Y | DateTag::Yearly => self.checked_add_periods(date, offset.checked_mul(12)?),
+            DateTag::Q | DateTag::Quarterly => {
+                self.checked_add_periods(date, offset.checked_mul(3)?)
+            }
+            DateTag::M | DateTag::Monthly => self.checked_add_periods(date, offset),
+            DateTag::W | DateTag::Weekly | DateTag::Mw | DateTag::WeekOfMonth => {
+                date.checked_add_signed(TimeDelta::weeks(offset as i64))
+            }
+            DateTag::D
+            | DateTag::Daily
+            | DateTag::Wd
+            | DateTag::WeekDay
+            | DateTag::O
+            | DateTag::Ordinal
+            | DateTag::Ed
+            | DateTag::EpochDay
+            | DateTag::Es
+            | DateTag::EpochSeconds
+            | DateTag::B36
+            | DateTag::Base36
+            | DateTag::X
+            | DateTag::Hex => date.checked_add_signed(TimeDelta::days(offset as i64)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// NRF calendar: 4-5-4 weeks, ending on the Saturday nearest to January 31st
+    fn nrf() -> RetailCalendar {
+        RetailCalendar {
+            pattern: RetailPattern::P454,
+            year_end: YearEnd::Nearest,
+            end_month: 1,
+            year_name: YearName::Start,
+        }
+    }
+
+    #[test]
+    fn test_last_day() {
+        assert_eq!(nrf().last_day(2024), Some(ymd(2024, 2, 3)));
+        assert_eq!(nrf().last_day(2025), Some(ymd(2025, 2, 1)));
+        let c = RetailCalendar {
+            year_end: YearEnd::Last,
+            ..nrf()
+        };
+        assert_eq!(c.last_day(2024), Some(ymd(2024, 1, 27)));
+        assert_eq!(c.last_day(2025), Some(ymd(2025, 1, 25)));
+    }
+
+    #[test]
+    fn test_year_53_weeks() {
+        // the fiscal year ending on 2024-02-03 has 53 weeks
+        let (year, first, last) = nrf().year(&ymd(2024, 1, 31)).unwrap();
+        assert_eq!(
+            (year, first, last),
+            (2024, ymd(2023, 1, 29), ymd(2024, 2, 3))
+        );
+        assert_eq!(((last - first).num_days() + 1) / 7, 53);
+        let d = nrf().date(&ymd(2024, 2, 3)).unwrap();
+        assert_eq!((d.period, d.week, d.week_of_period), (12, 53, 5));
+    }
+
+    #[test]
+    fn test_date() {
+        let d = nrf().date(&ymd(2024, 4, 27)).unwrap();
+        assert_eq!(
+            d,
+            RetailDate {
+                year: 2024,
+                quarter: 1,
+                period: 3,
+                week: 12,
+                week_of_period: 3,
+                weekday: 7,
+                day_of_period: 21,
+                day_of_year: 84,
+            }
+        );
+        let d = nrf().date(&ymd(2024, 2, 4)).unwrap();
+        assert_eq!((d.year, d.period, d.week, d.weekday), (2024, 1, 1, 1));
+        // years can be named after the calendar year they end in
+        let c = RetailCalendar {
+            year_name: YearName::End,
+            ..nrf()
+        };
+        let d = c.date(&ymd(2024, 2, 4)).unwrap();
+        assert_eq!((d.year, d.period, d.week, d.weekday), (2025, 1, 1, 1));
+        let d = c.date(&ymd(2024, 2, 3)).unwrap();
+        assert_eq!((d.year, d.period, d.week), (2024, 12, 53));
+    }
+
+    #[test]
+    fn test_specifier() {
+        let date = ymd(2024, 4, 27);
+        assert_eq!(nrf().specifier(&date, None, 'y').unwrap(), "24");
+        assert_eq!(nrf().specifier(&date, None, 'm').unwrap(), "03");
+        assert_eq!(nrf().specifier(&date, Some('-'), 'm').unwrap(), "3");
+        assert_eq!(nrf().specifier(&date, None, 'K').unwrap(), "3");
+        assert_eq!(nrf().specifier(&date, None, 'j').unwrap(), "084");
+        assert_eq!(nrf().specifier(&date, None, 'b'), None);
+    }
+
+    #[test]
+    fn test_period() {
+        let c = nrf();
+        let date = ymd(2024, 4, 27);
+        let p = c.period(&DateTag::M, &date).unwrap();
+        assert_eq!(p, (ymd(2024, 4, 7), ymd(2024, 5, 4)));
+        let p = c.period(&DateTag::Q, &date).unwrap();
+        assert_eq!(p, (ymd(2024, 2, 4), ymd(2024, 5, 4)));
+        let p = c.period(&DateTag::W, &date).unwrap();
+        assert_eq!(p, (ymd(2024, 4, 21), ymd(2024, 4, 27)));
+        // the last period of a 53-week year has an extra week
+        let p = c.period(&DateTag::M, &ymd(2024, 1, 31)).unwrap();
+        assert_eq!(p, (ymd(2023, 12, 31), ymd(2024, 2, 3)));
+    }
+
+    #[test]
+    fn test_checked_add_periods_53_weeks() {
+        let c = nrf();
+        // from the extra week of fiscal 2023 to the last week of fiscal 2022
+        let d = c.checked_add_periods(&ymd(2024, 2, 3), -12).unwrap();
+        assert_eq!(d, ymd(2023, 1, 28));
+        // moving forward keeps the position within the period
+        let d = c.checked_add_periods(&ymd(2024, 1, 3), 1).unwrap();
+        assert_eq!(d, ymd(2024, 2, 7));
+        let d = c
+            .checked_add_offset(&ymd(2023, 12, 31), 1, &DateTag::Y)
+            .unwrap();
+        assert_eq!(d, ymd(2025, 1, 5));
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    datestyle::DateStyle,
+    datetag::DateTag,
+    finder::{Finder, Found},
+    locale::DateFormatter,
+};
+
+/// arguments of the scan subcommand
+#[derive(Debug, clap::Args)]
+pub struct ScanArgs {
+    /// Directory to scan, using the current directory if not specified
+    dir: Option<PathBuf>,
+
+    /// Period of the groups of entries, non-daily tags being grouped by
+    /// their first day
+    #[arg(value_enum, long, default_value_t = DateTag::Monthly)]
+    group_by: DateTag,
+
+    /// Layout of the report
+    #[arg(value_enum, long, default_value_t = View::Tree)]
+    view: View,
+
+    /// Keep entries whose name starts with the given label, followed by the tag
+    #[arg(long)]
+    prefix: Option<String>,
+
+    /// Keep entries whose name continues with the given label after the tag
+    #[arg(long)]
+    suffix: Option<String>,
+
+    /// Keep entries whose tag period ends on or after the given date
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+
+    /// Keep entries whose tag period starts on or before the given date
+    #[arg(long, value_name = "DATE")]
+    until: Option<String>,
+
+    /// Maximum depth of the scanned subdirectories (0: only the directory entries)
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+}
+
+/// layouts of the scan report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum View {
+    /// entries indented under their group
+    Tree,
+    /// one aligned row per entry, with its group
+    Table,
+    /// JSON array of groups, with their entries
+    Json,
+}
+
+/// directory entry whose name contains a date tag
+#[derive(Debug, Clone)]
+pub struct Tagged {
+    /// path of the entry, relative to the scanned directory
+    pub path: PathBuf,
+    /// name of the entry
+    pub name: String,
+    /// first tag of the name
+    pub found: Found,
+}
+
+impl Tagged {
+    /// tagged entry of a path, whose name contains a date tag
+    pub fn from_path(path: PathBuf, finder: &Finder) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().into_owned();
+        let found = finder.find(&name)?;
+
+        Some(Tagged { path, name, found })
+    }
+
+    /// label before the tag (e.g. 'backup_')
+    pub fn prefix(&self) -> &str {
+        &self.name[..self.found.start]
+    }
+
+    /// label after the tag, extensions included (e.g. '_rel.tar')
+    pub fn suffix(&self) -> &str {
+        &self.name[self.found.end..]
+    }
+}
+
+/// entries of a directory tree whose name contains a date tag, sorted by path
+///
+/// Symbolic links are reported but not followed.
+pub fn entries(dir: &Path, max_depth: Option<usize>, finder: &Finder) -> Result<Vec<Tagged>> {
+    let mut tagged = Vec::new();
+    walk(dir, Path::new(""), 0, max_depth, finder, &mut tagged)?;
+
+    Ok(tagged)
+}
+
+fn walk(
+    dir: &Path,
+    relative: &Path,
+    depth: usize,
+    max_depth: Option<usize>,
+    finder: &Finder,
+    tagged: &mut Vec<Tagged>,
+) -> Result<()> {
+    let path = dir.join(relative);
+    let mut children = fs::read_dir(&path)
+        .with_context(|| format!("cannot read directory '{}'", path.display()))?
+        .collect::<Result<Vec<_>, _>>()?;
+    children.sort_by_key(|entry| entry.file_name());
+
+    for entry in children {
+        let relative = relative.join(entry.file_name());
+        tagged.extend(Tagged::from_path(relative.clone(), finder));
+
+        if entry.file_type()?.is_dir() && max_depth.is_none_or(|max| depth < max) {
+            walk(dir, &relative, depth + 1, max_depth, finder, tagged)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// entry of the scan report
+#[derive(Debug, Serialize)]
+struct Entry {
+    path: String,
+    tag: String,
+    tag_type: &'static str,
+    style: String,
+    date: NaiveDate,
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+}
+
+/// entries of a period
+#[derive(Debug, Serialize)]
+struct Group {
+    group: String,
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+    entries: Vec<Entry>,
+}
+
+/// report the tagged entries of a directory tree, grouped by period
+pub fn run(
+    args: &ScanArgs,
+    finder: &Finder,
+    parse: impl Fn(&str) -> Result<NaiveDate>,
+    formatter: &DateFormatter,
+    style: &DateStyle,
+) -> Result<String> {
+    let since = args.since.as_deref().map(&parse).transpose()?;
+    let until = args.until.as_deref().map(&parse).transpose()?;
+    let dir = args.dir.clone().unwrap_or_else(|| PathBuf::from("."));
+    let calendar = &formatter.calendar;
+
+    let mut groups: Vec<Group> = Vec::new();
+    for tagged in entries(&dir, args.max_depth, finder)? {
+        let found = &tagged.found;
+        let Some((period_start, period_end)) = found.tag_type.period(&found.date, calendar) else {
+            continue;
+        };
+
+        if args.prefix.as_ref().is_some_and(|p| tagged.prefix() != p)
+            || args
+                .suffix
+                .as_ref()
+                .is_some_and(|s| !tagged.suffix().starts_with(s))
+            || since.is_some_and(|since| period_end < since)
+            || until.is_some_and(|until| period_start > until)
+        {
+            continue;
+        }
+
+        let entry = Entry {
+            path: tagged.path.display().to_string(),
+            tag: found.text.clone(),
+            tag_type: found.tag_type.name(),
+            style: found
+                .style
+                .to_possible_value()
+                .map(|p| p.get_name().to_string())
+                .unwrap_or_default(),
+            date: found.date,
+            period_start,
+            period_end,
+        };
+
+        let Some((start, end)) = args.group_by.period(&found.date, calendar) else {
+            continue;
+        };
+        match groups.iter_mut().find(|g| g.period_start == start) {
+            Some(group) => group.entries.push(entry),
+            None => groups.push(Group {
+                group: formatter.format(&start, &args.group_by.get_format(style)),
+                period_start: start,
+                period_end: end,
+                entries: vec![entry],
+            }),
+        }
+    }
+
+    groups.sort_by_key(|g| g.period_start);
+    for group in &mut groups {
+        group
+            .entries
+            .sort_by(|a, b| (a.date, &a.path).cmp(&(b.date, &b.path)));
+    }
+
+    let mut report = String::new();
+    match args.view {
+        View::Tree => {
+            for group in &groups {
+                writeln!(report, "{} ({})", group.group, group.entries.len())?;
+                for entry in &group.entries {
+                    writeln!(report, "  {}", entry.path)?;
+                }
+            }
+        }
+        View::Table => {
+            let rows: Vec<[&str; 4]> = groups
+                .iter()
+                .flat_map(|g| {
+                    g.entries.iter().map(|e| {
+                        [
+                            g.group.as_str(),
+                            e.tag.as_str(),
+                            e.tag_type,
+                            e.path.as_str(),
+                        ]
+                    })
+                })
+                .collect();
+            write_table(&mut report, ["GROUP", "TAG", "TYPE", "PATH"], &rows)?;
+        }
+        View::Json => {
+            report = serde_json::to_string_pretty(&groups)?;
+            report.push('\n');
+        }
+    }
+
+    Ok(report)
+}
+
+/// write rows as left-aligned columns, separated by two spaces
+fn write_table(report: &mut String, header: [&str; 4], rows: &[[&str; 4]]) -> Result<()> {
+    let mut widths = header.map(|h| h.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(report, "{}", line.trim_end())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::utils::ParseOptions;
+
+    #[test]
+    fn test_entries() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        fs::create_dir_all(dir.join("logs/2024")).unwrap();
+        fs::write(dir.join("backup_20240427.tar"), "").unwrap();
+        fs::write(dir.join("logs/app_2024-04-03.log"), "").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let finder = Finder::new(&ParseOptions::default());
+        let paths = |max_depth| -> Vec<String> {
+            entries(&dir, max_depth, &finder)
+                .unwrap()
+                .iter()
+                .map(|t| t.path.display().to_string())
+                .collect()
+        };
+        assert_eq!(
+            paths(None),
+            vec![
+                "backup_20240427.tar",
+                "logs/2024",
+                "logs/app_2024-04-03.log"
+            ]
+        );
+        assert_eq!(paths(Some(0)), vec!["backup_20240427.tar"]);
+
+        let tagged = &entries(&dir, None, &finder).unwrap()[0];
+        assert_eq!((tagged.prefix(), tagged.suffix()), ("backup_", ".tar"));
+    }
+
+    #[test]
+    fn test_write_table() {
+        let mut report = String::new();
+        write_table(
+            &mut report,
+            ["GROUP", "TAG", "TYPE", "PATH"],
+            &[["202404", "20240427", "daily", "a"]],
+        )
+        .unwrap();
+        assert_eq!(
+            report,
+            "GROUP   TAG       TYPE   PATH\n202404  20240427  daily  a\n"
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use std::sync::LazyLock;
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use regex::Regex;
+
+/// maximum length of OCI image tags
+const OCI_TAG_MAX_LEN: usize = 128;
+
+/// PEP 440 versions, along with the alternate spellings allowed by the
+/// normalization rules (e.g. 'v1.0-alpha_1')
+static PEP440: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?ix)^\s*v?
+        (?:(?P<epoch>[0-9]+)!)?
+        (?P<release>[0-9]+(?:\.[0-9]+)*)
+        (?:[-_.]?(?P<pre_l>alpha|a|beta|b|preview|pre|c|rc)[-_.]?(?P<pre_n>[0-9]+)?)?
+        (?:-(?P<post_n1>[0-9]+)|[-_.]?(?P<post_l>post|rev|r)[-_.]?(?P<post_n2>[0-9]+)?)?
+        (?:[-_.]?(?P<dev_l>dev)[-_.]?(?P<dev_n>[0-9]+)?)?
+        (?:\+(?P<local>[a-z0-9]+(?:[-_.][a-z0-9]+)*))?
+        \s*$",
+    )
+    .unwrap()
+});
+
+/// packaging ecosystems whose version grammar date tags must follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Target {
+    /// Semantic Versioning 2.0.0, leading zeros are removed
+    Semver,
+    /// Python versions, in normalized form
+    Pep440,
+    /// Debian package versions, '_' being replaced with '.'
+    Debian,
+    /// RPM package versions, '-' being replaced with '.'
+    Rpm,
+    /// OCI image tags (e.g. Docker), invalid characters being replaced with '_'
+    OciTag,
+}
+
+impl Target {
+    /// name of the versions of the ecosystem
+    pub fn label(&self) -> &'static str {
+        match self {
+            Target::Semver => "SemVer version",
+            Target::Pep440 => "PEP 440 version",
+            Target::Debian => "Debian version",
+            Target::Rpm => "RPM version",
+            Target::OciTag => "OCI tag",
+        }
+    }
+
+    /// check a tag against the grammar of the ecosystem, normalizing it
+    /// where possible
+    pub fn normalize(&self, tag: &str) -> Result<String> {
+        if tag.is_empty() {
+            bail!("empty {}", self.label());
+        }
+
+        match self {
+            Target::Semver => semver(tag),
+            Target::Pep440 => pep440(tag),
+            Target::Debian => debian(&tag.replace('_', ".")),
+            Target::Rpm => rpm(&tag.replace('-', ".")),
+            Target::OciTag => oci_tag(tag),
+        }
+    }
+}
+
+/// remove leading zeros of a number
+fn trim_zeros(s: &str) -> &str {
+    match s.trim_start_matches('0') {
+        "" => "0",
+        s => s,
+    }
+}
+
+/// check that a string only contains the given characters, besides ASCII
+/// alphanumeric ones
+fn check_chars(s: &str, allowed: &str, what: &str) -> Result<()> {
+    match s
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !allowed.contains(*c))
+    {
+        Some(c) => bail!("invalid character '{}' in {} '{}'", c, what, s),
+        None => Ok(()),
+    }
+}
+
+/// MAJOR.MINOR.PATCH[-PRERELEASE][+BUILD]
+fn semver(tag: &str) -> Result<String> {
+    let (version, build) = match tag.split_once('+') {
+        Some((version, build)) => (version, Some(build)),
+        None => (tag, None),
+    };
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+
+    let numbers: Vec<&str> = core.split('.').collect();
+    if numbers.len() != 3 {
+        bail!(
+            "SemVer requires MAJOR.MINOR.PATCH, found {} number(s) in '{}'",
+            numbers.len(),
+            core
+        );
+    }
+    for (number, name) in numbers.iter().zip(["major", "minor", "patch"]) {
+        if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+            bail!("invalid {} number '{}'", name, number);
+        }
+    }
+    let mut normalized = numbers
+        .iter()
+        .map(|n| trim_zeros(n))
+        .collect::<Vec<_>>()
+        .join(".");
+
+    if let Some(pre) = pre {
+        let identifiers = pre
+            .split('.')
+            .map(|id| {
+                if id.is_empty() {
+                    bail!("empty pre-release identifier in '{}'", pre);
+                }
+                check_chars(id, "-", "pre-release identifier")?;
+                // numeric identifiers cannot have leading zeros
+                if id.chars().all(|c| c.is_ascii_digit()) {
+                    Ok(trim_zeros(id))
+                } else {
+                    Ok(id)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        normalized = format!("{}-{}", normalized, identifiers.join("."));
+    }
+
+    if let Some(build) = build {
+        for id in build.split('.') {
+            if id.is_empty() {
+                bail!("empty build identifier in '{}'", build);
+            }
+            check_chars(id, "-", "build identifier")?;
+        }
+        normalized = format!("{}+{}", normalized, build);
+    }
+
+    Ok(normalized)
+}
+
+/// [N!]N(.N)*[{a|b|rc}N][.postN][.devN][+local], in normalized form
+fn pep440(tag: &str) -> Result<String> {
+    let Some(caps) = PEP440.captures(tag) else {
+        bail!("PEP 440 requires [N!]N(.N)*[{{a|b|rc}}N][.postN][.devN][+local]");
+    };
+    let number = |name: &str| caps.name(name).map_or("0", |m| trim_zeros(m.as_str()));
+
+    let mut normalized = String::new();
+    if let Some(epoch) = caps.name("epoch") {
+        if trim_zeros(epoch.as_str()) != "0" {
+            normalized.push_str(&format!("{}!", trim_zeros(epoch.as_str())));
+        }
+    }
+    normalized.push_str(
+        &caps["release"]
+            .split('.')
+            .map(trim_zeros)
+            .collect::<Vec<_>>()
+            .join("."),
+    );
+
+    if let Some(pre) = caps.name("pre_l") {
+        let pre = match pre.as_str().to_lowercase().as_str() {
+            "alpha" | "a" => "a",
+            "beta" | "b" => "b",
+            _ => "rc",
+        };
+        normalized.push_str(&format!("{}{}", pre, number("pre_n")));
+    }
+    if let Some(post) = caps.name("post_n1") {
+        normalized.push_str(&format!(".post{}", trim_zeros(post.as_str())));
+    } else if caps.name("post_l").is_some() {
+        normalized.push_str(&format!(".post{}", number("post_n2")));
+    }
+    if caps.name("dev_l").is_some() {
+        normalized.push_str(&format!(".dev{}", number("dev_n")));
+    }
+    if let Some(local) = caps.name("local") {
+        let parts: Vec<String> = local
+            .as_str()
+            .split(['-', '_', '.'])
+            .map(|p| {
+                if p.chars().all(|c| c.is_ascii_digit()) {
+                    trim_zeros(p).to_string()
+                } else {
+                    p.to_lowercase()
+                }
+            })
+            .collect();
+        normalized.push_str(&format!("+{}", parts.join(".")));
+    }
+
+    Ok(normalized)
+}
+
+/// [EPOCH:]UPSTREAM[-REVISION]
+fn debian(tag: &str) -> Result<String> {
+    let (epoch, version) = match tag.split_once(':') {
+        Some((epoch, version)) => (Some(epoch), version),
+        None => (None, tag),
+    };
+    let (upstream, revision) = match version.rsplit_once('-') {
+        Some((upstream, revision)) => (upstream, Some(revision)),
+        None => (version, None),
+    };
+
+    if let Some(epoch) = epoch {
+        if epoch.is_empty() || !epoch.chars().all(|c| c.is_ascii_digit()) {
+            bail!("Debian epochs must be numbers, found '{}'", epoch);
+        }
+    }
+    if !upstream.starts_with(|c: char| c.is_ascii_digit()) {
+        bail!(
+            "Debian upstream versions must start with a digit, found '{}'",
+            upstream
+        );
+    }
+    check_chars(upstream, ".+~-", "upstream version")?;
+    if let Some(revision) = revision {
+        if revision.is_empty() {
+            bail!("empty Debian revision");
+        }
+        check_chars(revision, ".+~", "Debian revision")?;
+    }
+
+    Ok(tag.to_string())
+}
+
+/// version field of RPM packages
+fn rpm(tag: &str) -> Result<String> {
+    check_chars(tag, "._+~^", "RPM version")?;
+
+    Ok(tag.to_string())
+}
+
+/// [A-Za-z0-9_][A-Za-z0-9_.-]{0,127}
+fn oci_tag(tag: &str) -> Result<String> {
+    let normalized: String = tag
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "_.-".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if let Some(c) = normalized.chars().next().filter(|c| ".-".contains(*c)) {
+        bail!("OCI tags cannot start with '{}'", c);
+    }
+    if normalized.len() > OCI_TAG_MAX_LEN {
+        bail!(
+            "OCI tags are limited to {} characters, found {}",
+            OCI_TAG_MAX_LEN,
+            normalized.len()
+        );
+    }
+
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(target: Target, tag: &str) -> String {
+        target.normalize(tag).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_semver() {
+        let t = Target::Semver;
+        assert_eq!(t.normalize("2024.04.27").unwrap(), "2024.4.27");
+        assert_eq!(
+            t.normalize("1.0.0-rc.0427.a-1+build.007").unwrap(),
+            "1.0.0-rc.427.a-1+build.007"
+        );
+        assert_eq!(t.normalize("1.2.0-20240427").unwrap(), "1.2.0-20240427");
+        assert_eq!(
+            error(t, "202404"),
+            "SemVer requires MAJOR.MINOR.PATCH, found 1 number(s) in '202404'"
+        );
+        assert_eq!(error(t, "v1.2.3"), "invalid major number 'v1'");
+        assert_eq!(
+            error(t, "1.2.3-rc..1"),
+            "empty pre-release identifier in 'rc..1'"
+        );
+        assert_eq!(
+            error(t, "1.2.3-rc_1"),
+            "invalid character '_' in pre-release identifier 'rc_1'"
+        );
+        assert_eq!(error(t, "1.2.3+"), "empty build identifier in ''");
+    }
+
+    #[test]
+    fn test_pep440() {
+        let t = Target::Pep440;
+        assert_eq!(t.normalize("2024.04.27").unwrap(), "2024.4.27");
+        assert_eq!(t.normalize("v2024.04").unwrap(), "2024.4");
+        assert_eq!(t.normalize("0!1.0-Alpha_1").unwrap(), "1.0a1");
+        assert_eq!(t.normalize("1.0-preview2").unwrap(), "1.0rc2");
+        assert_eq!(t.normalize("2024.04-1").unwrap(), "2024.4.post1");
+        assert_eq!(t.normalize("1.0.rev.dev").unwrap(), "1.0.post0.dev0");
+        assert_eq!(
+            t.normalize("1!2024.4+Build-007_x").unwrap(),
+            "1!2024.4+build.7.x"
+        );
+        assert!(error(t, "2024-04-27").starts_with("PEP 440 requires"));
+        assert!(t.normalize("2024.04.27_rel").is_err());
+    }
+
+    #[test]
+    fn test_debian() {
+        let t = Target::Debian;
+        assert_eq!(t.normalize("1:2024.04.27-1").unwrap(), "1:2024.04.27-1");
+        assert_eq!(t.normalize("2024_04_27~rc1").unwrap(), "2024.04.27~rc1");
+        assert_eq!(t.normalize("2024-04-27").unwrap(), "2024-04-27");
+        assert_eq!(
+            error(t, "rel-2024"),
+            "Debian upstream versions must start with a digit, found 'rel'"
+        );
+        assert_eq!(
+            error(t, "x:2024"),
+            "Debian epochs must be numbers, found 'x'"
+        );
+        assert_eq!(
+            error(t, "2024/04-1"),
+            "invalid character '/' in upstream version '2024/04'"
+        );
+    }
+
+    #[test]
+    fn test_rpm() {
+        let t = Target::Rpm;
+        assert_eq!(t.normalize("2024-04-27").unwrap(), "2024.04.27");
+        assert_eq!(t.normalize("2024.04~rc1^git1").unwrap(), "2024.04~rc1^git1");
+        assert_eq!(
+            error(t, "2024:04"),
+            "invalid character ':' in RPM version '2024:04'"
+        );
+    }
+
+    #[test]
+    fn test_oci_tag() {
+        let t = Target::OciTag;
+        assert_eq!(t.normalize("build-2024.04.27").unwrap(), "build-2024.04.27");
+        assert_eq!(t.normalize("1.0+2024/04").unwrap(), "1.0_2024_04");
+        assert_eq!(error(t, "-2024"), "OCI tags cannot start with '-'");
+        assert_eq!(
+            error(t, &"a".repeat(129)),
+            "OCI tags are limited to 128 characters, found 129"
+        );
+        assert_eq!(error(t, ""), "empty OCI tag");
+    }
+}
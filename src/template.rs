@@ -0,0 +1,318 @@
+//! Tag templates, composing date references with other placeholders.
+//!
+//! A template is a string containing placeholders enclosed in braces
+//! (e.g. `{env:PROJECT}-{date:%Y%m}-{seq:03}-{git:branch}`), use `{{`
+//! and `}}` to insert literal braces. Supported placeholders:
+//!  * `{date}`, `{date:FORMAT}`: reference date (default tag format)
+//!  * `{start}`, `{start:FORMAT}`: first day of the tagged period
+//!  * `{end}`, `{end:FORMAT}`: last day of the tagged period
+//!  * `{seq}`, `{seq:WIDTH}`: sequence index, starting from 0
+//!    (zero-padded if WIDTH starts with '0')
+//!  * `{env:NAME}`: value of an environment variable
+//!  * `{hostname}`, `{user}`: current host and user names
+//!  * `{git:branch}`, `{git:hash}`: current git branch or short commit hash
+
+use std::{env, fs, process::Command};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::NaiveDate;
+
+use crate::{formats, locale::DateFormatter};
+
+/// placeholder found in a template string
+#[derive(Debug, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Date(Option<String>),
+    Start(Option<String>),
+    End(Option<String>),
+    Seq { width: usize, zero: bool },
+    Env(String),
+    Hostname,
+    User,
+    GitBranch,
+    GitHash,
+}
+
+/// parsed template, with all environment-related values already resolved
+#[derive(Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// parse a template string and resolve environment-related placeholders,
+    /// reading environment variables through the `env` lookup
+    pub fn new(s: &str, env: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let parts = parse(s)?
+            .into_iter()
+            .map(|p| {
+                Ok(match p {
+                    Part::Env(name) => Part::Literal(
+                        env(&name)
+                            .with_context(|| format!("environment variable '{}' not set", name))?,
+                    ),
+                    Part::Hostname => Part::Literal(hostname()?),
+                    Part::User => Part::Literal(user()?),
+                    Part::GitBranch => Part::Literal(git(&["rev-parse", "--abbrev-ref", "HEAD"])?),
+                    Part::GitHash => Part::Literal(git(&["rev-parse", "--short", "HEAD"])?),
+                    p => p,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Template { parts })
+    }
+
+    /// render the template for the given date, period and sequence index
+    pub fn render(
+        &self,
+        date: &NaiveDate,
+        period: (NaiveDate, NaiveDate),
+        index: usize,
+        format: &str,
+        formatter: &DateFormatter,
+    ) -> String {
+        let mut s = String::new();
+
+        for p in &self.parts {
+            match p {
+                Part::Literal(l) => s.push_str(l),
+                Part::Date(f) => {
+                    s.push_str(&formatter.format(date, f.as_deref().unwrap_or(format)))
+                }
+                Part::Start(f) => {
+                    s.push_str(&formatter.format(&period.0, f.as_deref().unwrap_or(format)))
+                }
+                Part::End(f) => {
+                    s.push_str(&formatter.format(&period.1, f.as_deref().unwrap_or(format)))
+                }
+                Part::Seq { width, zero: true } => s.push_str(&format!("{:0w$}", index, w = width)),
+                Part::Seq { width, zero: false } => s.push_str(&format!("{:w$}", index, w = width)),
+                // environment-related placeholders are resolved on creation
+                _ => unreachable!(),
+            }
+        }
+
+        s
+    }
+}
+
+/// split a template string into literals and placeholders
+fn parse(s: &str) -> Result<Vec<Part>> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().is_some_and(|&(_, n)| n == '{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek().is_some_and(|&(_, n)| n == '}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                // collect the placeholder up to the closing brace
+                let mut end = None;
+                for (j, n) in chars.by_ref() {
+                    if n == '}' {
+                        end = Some(j);
+                        break;
+                    }
+                }
+                let end = end.ok_or_else(|| {
+                    anyhow!("template error at position {}: unclosed placeholder", i + 1)
+                })?;
+
+                if !literal.is_empty() {
+                    parts.push(Part::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(
+                    placeholder(&s[i + 1..end])
+                        .with_context(|| format!("template error at position {}", i + 1))?,
+                );
+            }
+            '}' => bail!("template error at position {}: unmatched '}}'", i + 1),
+            c => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        parts.push(Part::Literal(literal));
+    }
+
+    Ok(parts)
+}
+
+/// convert the content of a single placeholder
+fn placeholder(s: &str) -> Result<Part> {
+    let (name, arg) = match s.split_once(':') {
+        Some((n, a)) => (n, Some(a)),
+        None => (s, None),
+    };
+
+    let part = match (name, arg) {
+        ("date", f) => Part::Date(format_arg(f)?),
+        ("start", f) => Part::Start(format_arg(f)?),
+        ("end", f) => Part::End(format_arg(f)?),
+        ("seq", None) => Part::Seq {
+            width: 0,
+            zero: false,
+        },
+        ("seq", Some(w)) => Part::Seq {
+            width: w
+                .parse()
+                .map_err(|_| anyhow!("invalid sequence width '{}'", w))?,
+            zero: w.starts_with('0'),
+        },
+        ("env", Some(v)) if !v.is_empty() => Part::Env(v.to_string()),
+        ("env", _) => bail!("missing environment variable name"),
+        ("hostname", None) => Part::Hostname,
+        ("user", None) => Part::User,
+        ("git", Some("branch")) => Part::GitBranch,
+        ("git", Some("hash")) => Part::GitHash,
+        ("git", a) => bail!("unknown git reference '{}'", a.unwrap_or_default()),
+        _ => bail!("unknown placeholder '{{{}}}'", s),
+    };
+
+    Ok(part)
+}
+
+fn format_arg(f: Option<&str>) -> Result<Option<String>> {
+    match f {
+        Some("") => bail!("empty date format"),
+        Some(f) => {
+            formats::validate(f)?;
+            Ok(Some(String::from(f)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// retrieve the current host name
+fn hostname() -> Result<String> {
+    env::var("HOSTNAME")
+        .or_else(|_| env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .or_else(|| command("hostname", &[]).ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .context("unable to retrieve host name")
+}
+
+/// retrieve the current user name
+fn user() -> Result<String> {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .or_else(|_| env::var("LOGNAME"))
+        .ok()
+        .or_else(|| command("whoami", &[]).ok())
+        .filter(|u| !u.is_empty())
+        .context("unable to retrieve user name")
+}
+
+/// retrieve information from the git repository of the current directory
+fn git(args: &[&str]) -> Result<String> {
+    command("git", args).context("unable to retrieve git information")
+}
+
+fn command(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program).args(args).output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::Calendar;
+    use chrono::Locale;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// environment lookup of the tests, leaving the process environment as is
+    fn env(name: &str) -> Option<String> {
+        (name == "PROJECT").then(|| String::from("PRJ"))
+    }
+
+    fn render(s: &str, index: usize) -> String {
+        let period = (ymd(2024, 4, 1), ymd(2024, 4, 30));
+        let formatter = DateFormatter {
+            locale: Locale::POSIX,
+            calendar: Calendar::default(),
+        };
+        Template::new(s, env)
+            .unwrap()
+            .render(&ymd(2024, 4, 27), period, index, "%Y%m", &formatter)
+    }
+
+    #[test]
+    fn test_parse_literal_and_escapes() {
+        let p = parse("a{{b}}c").unwrap();
+        assert_eq!(p, vec![Part::Literal(String::from("a{b}c"))]);
+    }
+
+    #[test]
+    fn test_parse_placeholders() {
+        let p = parse("{env:X}-{date:%Y}{seq:03}{git:hash}").unwrap();
+        assert_eq!(
+            p,
+            vec![
+                Part::Env(String::from("X")),
+                Part::Literal(String::from("-")),
+                Part::Date(Some(String::from("%Y"))),
+                Part::Seq {
+                    width: 3,
+                    zero: true
+                },
+                Part::GitHash,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let e = parse("ab{date").unwrap_err();
+        assert!(e.to_string().contains("position 3"));
+        let e = parse("ab}").unwrap_err();
+        assert!(e.to_string().contains("position 3"));
+        let e = parse("{foo}").unwrap_err();
+        assert!(format!("{:#}", e).contains("unknown placeholder '{foo}'"));
+        assert!(parse("{seq:x}").is_err());
+        assert!(parse("{env:}").is_err());
+        assert!(parse("{git:tag}").is_err());
+        assert!(parse("{date:}").is_err());
+        let e = parse("{seq}{date:%Y%H}").unwrap_err();
+        assert!(format!("{:#}", e).contains("position 6: specifier '%H' at position 3"));
+    }
+
+    #[test]
+    fn test_render_dates() {
+        assert_eq!(render("{date}", 0), "202404");
+        assert_eq!(render("{date:%d.%m.%Y}", 0), "27.04.2024");
+        assert_eq!(render("{start:%d}-{end:%d}", 0), "01-30");
+    }
+
+    #[test]
+    fn test_render_seq() {
+        assert_eq!(render("{seq}", 7), "7");
+        assert_eq!(render("{seq:03}", 7), "007");
+        assert_eq!(render("{seq:3}", 7), "  7");
+    }
+
+    #[test]
+    fn test_render_env() {
+        assert_eq!(render("{env:PROJECT}-{date}", 0), "PRJ-202404");
+        let e = Template::new("{env:UNSET}", env).unwrap_err();
+        assert_eq!(e.to_string(), "environment variable 'UNSET' not set");
+    }
+}
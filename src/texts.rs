@@ -16,6 +16,71 @@ pub const EXAMPLES: &str = color_print::cstr!(
     2024.03.12
     2024.03.14
     2024.03.16
+
+    $ printf '20240312\n20240403\n' | <s>datetag</> --stdin -td -s dash
+    2024-03-12
+    2024-04-03
+
+    $ <s>datetag</> 20240427 -td -s hive -u 20240428 --mkdir data
+    year=2024/month=04/day=27
+    year=2024/month=04/day=28
+
+    $ <s>datetag</> 20240427 --calendar retail -t week-of-month
+    FY24-P03-W3
+
+    $ <s>datetag</> 20240427 --calendar japanese -td -s dot
+    R06.04.27
+
+    $ <s>datetag</> 20240427 -t daily -p LAB_ --unique --state /var/tmp/ci.state
+    LAB_20240427_02
+
+    $ <s>datetag</> scan backups --group-by yearly
+    2024 (2)
+      backup_2024-03-31.tar
+      backup_20240427.tar
+
+    $ <s>datetag</> prune backups --keep-daily 1 --keep-monthly 2 --action delete
+    keep    backup_20240427.tar (daily, monthly)
+    keep    backup_20240331.tar (monthly)
+    delete  backup_20240315.tar
+    dry run: 1 entries would be pruned, use '--apply' to proceed
+
+    $ <s>datetag</> -td rename IMG_0042.jpg --source metadata --apply
+    rename  IMG_0042.jpg -> 20240427_IMG_0042.jpg
+
+    $ <s>datetag</> -td -s dash normalize shared
+    -2024.04.03_x
+    +2024-04-03_x
+    dry run: 1 entries would be renamed, use '--apply' to proceed
+
+    $ <s>datetag</> -tm -s slash archive --root archive report.pdf --apply
+    move    report.pdf -> archive/2024/04/report.pdf
+    1 files moved, 0 skipped
+
+    $ ls | <s>datetag</> filter --since 20240401 | <s>datetag</> sort --reverse
+    app_2024-04-27.log
+    LAB_202404_rel
+
+    $ ls exports | <s>datetag</> -td -s dash gaps --stdin
+    missing       2024-04-03
+    3 expected, 1 missing, 0 duplicates, 0 unexpected, 0 out of range
+    Error: 1 missing periods
+
+    $ <s>datetag</> extract CHANGELOG.md --strictness strict | column -t
+    CHANGELOG.md:3:14  2024-04-27  2024-04-27  daily   2024-04-27..2024-04-27
+    CHANGELOG.md:4:7   2024-W17    2024-04-22  weekly  2024-04-22..2024-04-28
+
+    $ <s>datetag</> 20240427 -t base36 -p cache_
+    cache_fb4
+
+    $ <s>datetag</> @fb4 -t base36 --format %F
+    2024-04-27
+
+    $ git tag --list | <s>datetag</> calver YY.0M.MICRO 20240427 --stdin
+    24.04.2
+
+    $ <s>datetag</> 20240427 -td -s dot -p 1.2.0-nightly. --target semver
+    1.2.0-nightly.2024.4.27
 "#
 );
 
@@ -23,6 +88,21 @@ pub const NOTES: &str = color_print::cstr!(
     r#"<s><u>Notes</u></s>:
     Argument '--format' use string format from:
     https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+    only date specifiers are supported (see '--list-formats')
+    with '--calendar retail', years (%Y, %y, %G, %g), quarters (%q), months (%m),
+    weeks (%V, %K) and days (%u, %d, %j) are fiscal ones
+    with '--calendar hijri' or '--calendar hebrew', years (%Y, %y, %C), quarters (%q),
+    months (%m, %b, %B) and days (%d, %e, %j) are converted, weeks stay gregorian
+    with '--calendar japanese', years (%Y, %G) include the era letter (e.g. R06)
+
+    Argument '--template' supports the following placeholders:
+    {date}, {date:FORMAT}    reference date (default tag format)
+    {start}, {start:FORMAT}  first day of the tagged period
+    {end}, {end:FORMAT}      last day of the tagged period
+    {seq}, {seq:WIDTH}       sequence index, starting from 0 (e.g. {seq:03})
+    {env:NAME}               environment variable value
+    {hostname}, {user}       current host and user names
+    {git:branch}, {git:hash} current git branch or short commit hash
+    use '{{' and '}}' to insert literal braces
 "#
 );
-
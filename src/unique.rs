@@ -0,0 +1,246 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+
+/// default state file of unique tag counters
+pub const DEFAULT_STATE_FILE: &str = ".datetag-state";
+
+/// origin of the counters of unique tags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// counters stored in a state file, updated under an exclusive lock
+    State(PathBuf),
+    /// counters derived from the names of the entries of a directory
+    Dir(PathBuf),
+    /// counters derived from the tags of the local git repository
+    Git,
+}
+
+/// counters of unique tags, one per base tag (i.e. prefix and period)
+pub struct Counters {
+    source: Source,
+    /// last counter assigned to each base tag during this run
+    assigned: HashMap<String, u32>,
+    /// names already in use, for derived counters
+    names: Option<Vec<String>>,
+}
+
+impl Counters {
+    pub fn new(source: Source) -> Self {
+        Counters {
+            source,
+            assigned: HashMap::new(),
+            names: None,
+        }
+    }
+
+    /// append the next counter of a base tag (e.g. 'LAB_20240427_02')
+    pub fn unique(&mut self, base: &str) -> Result<String> {
+        let counter = match &self.source {
+            Source::State(path) => next_in_state(path, base)?,
+            Source::Dir(_) | Source::Git => {
+                let last = self.assigned.get(base).copied();
+                let counter = match last {
+                    Some(last) => last + 1,
+                    None => last_in_names(self.names()?, base) + 1,
+                };
+                self.assigned.insert(base.to_string(), counter);
+                counter
+            }
+        };
+
+        Ok(format!("{}_{:02}", base, counter))
+    }
+
+    /// names already in use, read once
+    fn names(&mut self) -> Result<&[String]> {
+        if self.names.is_none() {
+            self.names = Some(match &self.source {
+                Source::Dir(dir) => fs::read_dir(dir)
+                    .with_context(|| format!("cannot read directory '{}'", dir.display()))?
+                    .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+                    .collect::<Result<_>>()?,
+                Source::Git => git_tags()?,
+                Source::State(_) => Vec::new(),
+            });
+        }
+
+        Ok(self.names.as_deref().unwrap_or_default())
+    }
+}
+
+/// highest counter of a base tag among the given names, 0 if none
+///
+/// Names may have an extension (e.g. 'LAB_20240427_02.tar.gz').
+fn last_in_names(names: &[String], base: &str) -> u32 {
+    let regex = Regex::new(&format!(r"^{}_(\d+)(?:\..*)?$", regex::escape(base))).unwrap();
+
+    names
+        .iter()
+        .filter_map(|name| regex.captures(name)?[1].parse().ok())
+        .max()
+        .unwrap_or(0)
+}
+
+/// tags of the local git repository
+fn git_tags() -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["tag", "--list"])
+        .output()
+        .context("unable to retrieve git tags")?;
+    if !output.status.success() {
+        bail!(
+            "unable to retrieve git tags: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// path of a sibling of the state file (e.g. its lock file)
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+/// increment the counter of a base tag in the state file
+///
+/// The state file is only read and replaced while holding an exclusive
+/// lock on its lock file, and replaced atomically by renaming a temporary
+/// file, so concurrent processes never get the same counter.
+fn next_in_state(path: &Path, base: &str) -> Result<u32> {
+    let lock_path = sibling(path, ".lock");
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("cannot open lock file '{}'", lock_path.display()))?;
+    lock.lock()
+        .with_context(|| format!("cannot lock '{}'", lock_path.display()))?;
+
+    let mut counters = match fs::read_to_string(path) {
+        Ok(content) => parse_state(&content)
+            .with_context(|| format!("wrong state file '{}'", path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => {
+            return Err(e).with_context(|| format!("cannot read state file '{}'", path.display()))
+        }
+    };
+
+    let counter = match counters.iter_mut().find(|(key, _)| key == base) {
+        Some((_, counter)) => {
+            *counter += 1;
+            *counter
+        }
+        None => {
+            counters.push((base.to_string(), 1));
+            1
+        }
+    };
+
+    let tmp_path = sibling(path, ".tmp");
+    let mut tmp = File::create(&tmp_path)
+        .with_context(|| format!("cannot write state file '{}'", tmp_path.display()))?;
+    for (key, counter) in &counters {
+        writeln!(tmp, "{}\t{}", key, counter)?;
+    }
+    tmp.sync_all()?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("cannot replace state file '{}'", path.display()))?;
+
+    Ok(counter)
+}
+
+/// read the counters of a state file, made of 'tag<TAB>counter' lines
+fn parse_state(content: &str) -> Result<Vec<(String, u32)>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(number, line)| {
+            let (key, counter) = line
+                .rsplit_once('\t')
+                .with_context(|| format!("missing counter at line {}", number + 1))?;
+            let counter = counter
+                .parse()
+                .with_context(|| format!("wrong counter '{}' at line {}", counter, number + 1))?;
+            Ok((key.to_string(), counter))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_last_in_names() {
+        let n = names(&[
+            "LAB_20240427_01",
+            "LAB_20240427_03.tar.gz",
+            "LAB_20240427",
+            "LAB_20240426_07",
+            "LAB_20240427_x",
+        ]);
+        assert_eq!(last_in_names(&n, "LAB_20240427"), 3);
+        assert_eq!(last_in_names(&n, "LAB_20240428"), 0);
+        assert_eq!(last_in_names(&n, "LAB.2024"), 0);
+    }
+
+    #[test]
+    fn test_parse_state() {
+        let s = parse_state("LAB_20240427\t2\n\nREL 2024\t10\n").unwrap();
+        assert_eq!(
+            s,
+            vec![
+                ("LAB_20240427".to_string(), 2),
+                ("REL 2024".to_string(), 10)
+            ]
+        );
+        assert!(parse_state("LAB_20240427 2").is_err());
+        assert!(parse_state("LAB_20240427\tx").is_err());
+    }
+
+    #[test]
+    fn test_dir_counters() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        fs::create_dir_all(dir.join("LAB_20240427_04")).unwrap();
+
+        let mut c = Counters::new(Source::Dir(dir.to_path_buf()));
+        assert_eq!(c.unique("LAB_20240427").unwrap(), "LAB_20240427_05");
+        assert_eq!(c.unique("LAB_20240427").unwrap(), "LAB_20240427_06");
+        assert_eq!(c.unique("LAB_20240428").unwrap(), "LAB_20240428_01");
+    }
+
+    #[test]
+    fn test_state_counters() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.join("state");
+
+        let mut c = Counters::new(Source::State(path.clone()));
+        assert_eq!(c.unique("LAB_20240427").unwrap(), "LAB_20240427_01");
+        assert_eq!(c.unique("LAB_20240427").unwrap(), "LAB_20240427_02");
+        assert_eq!(c.unique("REL_20240427").unwrap(), "REL_20240427_01");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "LAB_20240427\t2\nREL_20240427\t1\n"
+        );
+    }
+}
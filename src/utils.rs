@@ -1,16 +1,121 @@
-use chrono::{Months, NaiveDate, TimeDelta};
+use std::sync::LazyLock;
+
+use chrono::{Locale, Months, NaiveDate, TimeDelta};
 use regex::Regex;
 
-use crate::datetag::DateTag;
+use crate::{
+    calendar::Calendar,
+    datestyle::{DateStyle, FieldOrder},
+    datetag::DateTag,
+    epoch::{self, Encoding},
+    locale,
+    weeks::{self, WeekSystem},
+};
+
+/// default century pivot: two-digit years from 69 belong to the 20th century
+pub const DEFAULT_CENTURY_PIVOT: u32 = 69;
+
+/// options driving the conversion of strings into dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// order of date fields
+    pub order: FieldOrder,
+    /// dates use two-digit years (e.g. 240427)
+    pub short_year: bool,
+    /// two-digit years below the pivot belong to the 21st century,
+    /// the others to the 20th century
+    pub pivot: u32,
+    /// locale of month names
+    pub locale: Locale,
+    /// numbering of weeks
+    pub weeks: WeekSystem,
+    /// dates are weeks, digits being read as YYWW with two-digit years
+    /// (e.g. 2417), instead of YYMM
+    pub weekly: bool,
+    /// encoding of dates prefixed by '@' (e.g. '@19840')
+    pub encoding: Encoding,
+    /// first day of encoded dates
+    pub epoch: NaiveDate,
+}
 
-pub fn try_date_from_str(s: &str) -> Result<NaiveDate, &'static str> {
-    checked_date_from_str(s).ok_or("conversion error")
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            order: FieldOrder::Ymd,
+            short_year: false,
+            pivot: DEFAULT_CENTURY_PIVOT,
+            locale: Locale::POSIX,
+            weeks: WeekSystem::Iso,
+            weekly: false,
+            encoding: Encoding::Days,
+            epoch: epoch::unix_epoch(),
+        }
+    }
 }
 
+impl ParseOptions {
+    /// parse dates matching the given date tag style
+    pub fn from_style(style: &DateStyle, pivot: u32, locale: Locale, weeks: WeekSystem) -> Self {
+        ParseOptions {
+            order: style.order,
+            short_year: style.short_year,
+            pivot,
+            locale,
+            weeks,
+            ..ParseOptions::default()
+        }
+    }
+
+    /// no option is set, apart from the decoding of '@' dates
+    fn is_default(&self) -> bool {
+        ParseOptions {
+            encoding: Encoding::Days,
+            epoch: epoch::unix_epoch(),
+            ..*self
+        } == ParseOptions::default()
+    }
+
+    /// convert a two-digit year using the century pivot
+    fn full_year(&self, year: i32) -> i32 {
+        year + if year < self.pivot as i32 { 2000 } else { 1900 }
+    }
+}
+
+pub fn try_date_from_str_with(s: &str, options: &ParseOptions) -> Result<NaiveDate, &'static str> {
+    checked_date_from_str_with(s, options).ok_or("conversion error")
+}
+
+/// matches any non-digit character, compiled once and shared by all conversions
+static NON_DIGITS: LazyLock<Regex> = LazyLock::new(|| Regex::new("[^0-9]").unwrap());
+
+/// matches the key of Hive partition path segments (e.g. 'year=')
+static HIVE_KEYS: LazyLock<Regex> = LazyLock::new(|| Regex::new("[^=/]*=").unwrap());
+
+/// matches quarterly dates (e.g. '2024Q2' or '2024-Q2')
+static QUARTER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(\d{4})[^0-9A-Za-z]?[Qq]([1-4])\s*$").unwrap());
+
+/// matches ISO 8601 week dates (e.g. '2024-W17-6' or '2024W17')
+static ISO_WEEK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(\d{4})[^0-9A-Za-z]?[Ww](\d{2})(?:[^0-9A-Za-z]?([1-7]))?\s*$").unwrap()
+});
+
+/// matches week of the month dates (e.g. '2024-04-W4' or '202404W4')
+static MONTH_WEEK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(\d{4})[^0-9A-Za-z]?(\d{2})[^0-9A-Za-z]?[Ww]([1-5])\s*$").unwrap()
+});
+
+/// matches ISO 8601 ordinal dates (e.g. '2024-118' or '2024118')
+static ORDINAL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(\d{4})[^0-9A-Za-z]?(\d{3})\s*$").unwrap());
+
+/// matches ordinal dates, day first (e.g. '118.2024')
+static ORDINAL_DAY_FIRST: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(\d{3})[^0-9A-Za-z](\d{4})\s*$").unwrap());
+
 pub fn checked_date_from_str(s: &str) -> Option<NaiveDate> {
     // remove any non-digit character
-    let re = Regex::new("[^0-9]").unwrap();
-    let mut temp = re.replace_all(s, "").to_string();
+    let mut temp = NON_DIGITS.replace_all(s, "").to_string();
 
     // add a month, if needed
     if temp.len() == 4 {
@@ -26,25 +131,192 @@ pub fn checked_date_from_str(s: &str) -> Option<NaiveDate> {
     NaiveDate::parse_from_str(&temp, "%Y%m%d").ok()
 }
 
-pub fn checked_add_offset(date: &NaiveDate, offset: i32, tag_type: &DateTag) -> Option<NaiveDate> {
+/// convert a string into a date, using the given field order and year width
+///
+/// Dates not matching the options are converted using the default format.
+pub fn checked_date_from_str_with(s: &str, options: &ParseOptions) -> Option<NaiveDate> {
+    // decode encoded dates (e.g. '@19840')
+    if let Some(value) = s.trim().strip_prefix('@') {
+        return options.encoding.decode(value, &options.epoch);
+    }
+
+    // drop Hive partition keys (e.g. 'year=2024/month=04')
+    let s = &HIVE_KEYS.replace_all(s, "");
+
+    if let Some(date) = checked_date_from_quarter(s)
+        .or_else(|| checked_date_from_week(s, options))
+        .or_else(|| checked_date_from_month_week(s))
+        .or_else(|| checked_date_from_ordinal(s, options))
+    {
+        return Some(date);
+    }
+
+    if s.chars().any(char::is_alphabetic) {
+        return checked_date_from_month_name(s, options);
+    }
+
+    if options.is_default() {
+        return checked_date_from_str(s);
+    }
+
+    checked_date_from_digits(&NON_DIGITS.replace_all(s, ""), options)
+        .or_else(|| checked_date_from_str(s))
+}
+
+/// convert a quarterly date (e.g. '2024Q2') into the first day of the quarter
+fn checked_date_from_quarter(s: &str) -> Option<NaiveDate> {
+    let caps = QUARTER.captures(s)?;
+    let quarter: u32 = caps[2].parse().ok()?;
+
+    NaiveDate::from_ymd_opt(caps[1].parse().ok()?, quarter * 3 - 2, 1)
+}
+
+/// convert a week date (e.g. '2024-W17-6') according to the week numbering
+/// system, weeks without a weekday are converted into their first day
+fn checked_date_from_week(s: &str, options: &ParseOptions) -> Option<NaiveDate> {
+    let caps = ISO_WEEK.captures(s)?;
+    let day = caps.get(3).and_then(|d| d.as_str().parse().ok());
+
+    options
+        .weeks
+        .date_of_week(caps[1].parse().ok()?, caps[2].parse().ok()?, day)
+}
+
+/// convert a week of the month date (e.g. '2024-04-W4') into the first
+/// day of the week
+fn checked_date_from_month_week(s: &str) -> Option<NaiveDate> {
+    let caps = MONTH_WEEK.captures(s)?;
+    let week: u32 = caps[3].parse().ok()?;
+
+    NaiveDate::from_ymd_opt(
+        caps[1].parse().ok()?,
+        caps[2].parse().ok()?,
+        (week - 1) * 7 + 1,
+    )
+}
+
+/// convert an ordinal date (e.g. '2024-118'), the day of the year may come
+/// first if the field order is not year-month-day
+///
+/// Ordinal dates without separators are ambiguous with two-digit years.
+fn checked_date_from_ordinal(s: &str, options: &ParseOptions) -> Option<NaiveDate> {
+    let captures = ORDINAL
+        .captures(s)
+        .filter(|c| !options.short_year || c[0].trim().len() > 7);
+
+    let (year, day) = match captures {
+        Some(caps) => (caps[1].parse().ok()?, caps[2].parse().ok()?),
+        None if options.order != FieldOrder::Ymd => {
+            let caps = ORDINAL_DAY_FIRST.captures(s)?;
+            (caps[2].parse().ok()?, caps[1].parse().ok()?)
+        }
+        None => return None,
+    };
+
+    NaiveDate::from_yo_opt(year, day)
+}
+
+fn checked_date_from_digits(digits: &str, options: &ParseOptions) -> Option<NaiveDate> {
+    let w = if options.short_year { 2 } else { 4 };
+
+    // two-digit year weeks (e.g. '2417'), ambiguous with months otherwise
+    if options.weekly && options.short_year && digits.len() == 4 {
+        let (y, week) = match options.order {
+            FieldOrder::Ymd => (&digits[..2], &digits[2..]),
+            _ => (&digits[2..], &digits[..2]),
+        };
+        let year = options.full_year(y.parse().ok()?);
+
+        return options.weeks.date_of_week(year, week.parse().ok()?, None);
+    }
+
+    // split year, month and day fields, missing ones default to 1
+    let (y, m, d) = match (digits.len().checked_sub(w)?, options.order) {
+        (0, _) => (digits, "1", "1"),
+        (2, FieldOrder::Ymd) => (&digits[..w], &digits[w..], "1"),
+        (2, _) => (&digits[2..], &digits[..2], "1"),
+        (4, FieldOrder::Ymd) => (&digits[..w], &digits[w..w + 2], &digits[w + 2..]),
+        (4, FieldOrder::Dmy) => (&digits[4..], &digits[2..4], &digits[..2]),
+        (4, FieldOrder::Mdy) => (&digits[4..], &digits[..2], &digits[2..4]),
+        _ => return None,
+    };
+
+    let mut year: i32 = y.parse().ok()?;
+    if options.short_year {
+        year = options.full_year(year);
+    }
+
+    NaiveDate::from_ymd_opt(year, m.parse().ok()?, d.parse().ok()?)
+}
+
+/// convert a string containing a month name (e.g. 'Apr2024' or '27 avril 2024')
+fn checked_date_from_month_name(s: &str, options: &ParseOptions) -> Option<NaiveDate> {
+    let (month, rest) = locale::find_month(s, options.locale)?;
+
+    // remaining digit groups are the year and, optionally, the day
+    let groups: Vec<&str> = rest
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|g| !g.is_empty())
+        .collect();
+
+    let (y, d) = match groups[..] {
+        [y] => (y, "1"),
+        // a long group, or the first one in year-month-day order, is the year
+        [a, b] if a.len() > 2 || (b.len() <= 2 && options.order == FieldOrder::Ymd) => (a, b),
+        [a, b] => (b, a),
+        _ => return None,
+    };
+
+    let mut year: i32 = y.parse().ok()?;
+    if y.len() <= 2 {
+        year = options.full_year(year);
+    }
+
+    NaiveDate::from_ymd_opt(year, month, d.parse().ok()?)
+}
+
+pub fn checked_add_offset_with(
+    date: &NaiveDate,
+    offset: i32,
+    tag_type: &DateTag,
+    calendar: &Calendar,
+) -> Option<NaiveDate> {
+    if let Some(date) = calendar.checked_add_offset(date, offset, tag_type) {
+        return date;
+    }
+
     // apply date offset
     match tag_type {
-        DateTag::Yearly | DateTag::Y => {
-            if offset > 0 {
-                date.checked_add_months(Months::new((offset * 12) as u32))
-            } else {
-                date.checked_sub_months(Months::new((-offset * 12) as u32))
-            }
-        }
-        DateTag::W | DateTag::Weekly => date.checked_add_signed(TimeDelta::days(7 * offset as i64)),
-        DateTag::Monthly | DateTag::M => {
-            if offset > 0 {
-                date.checked_add_months(Months::new(offset as u32))
-            } else {
-                date.checked_sub_months(Months::new(-offset as u32))
-            }
-        }
-        DateTag::Daily | DateTag::D => date.checked_add_signed(TimeDelta::days(offset as i64)),
+        DateTag::Yearly | DateTag::Y => checked_add_months(date, offset.checked_mul(12)?),
+        DateTag::Quarterly | DateTag::Q => checked_add_months(date, offset.checked_mul(3)?),
+        DateTag::W | DateTag::Weekly => calendar.weeks.checked_add_weeks(date, offset),
+        DateTag::Monthly | DateTag::M => checked_add_months(date, offset),
+        DateTag::Mw | DateTag::WeekOfMonth => weeks::checked_add_month_weeks(date, offset),
+        DateTag::Daily
+        | DateTag::D
+        | DateTag::WeekDay
+        | DateTag::Wd
+        | DateTag::Ordinal
+        | DateTag::O
+        | DateTag::Ed
+        | DateTag::EpochDay
+        | DateTag::Es
+        | DateTag::EpochSeconds
+        | DateTag::B36
+        | DateTag::Base36
+        | DateTag::X
+        | DateTag::Hex => date.checked_add_signed(TimeDelta::days(offset as i64)),
+    }
+}
+
+/// move a date by the given number of months, backwards if negative
+fn checked_add_months(date: &NaiveDate, months: i32) -> Option<NaiveDate> {
+    let delta = Months::new(months.unsigned_abs());
+
+    if months > 0 {
+        date.checked_add_months(delta)
+    } else {
+        date.checked_sub_months(delta)
     }
 }
 
@@ -53,6 +325,16 @@ mod tests {
     use super::*;
     use chrono::Datelike;
 
+    use crate::{datestyle::StylePreset, locale::DateFormatter};
+
+    fn try_date_from_str(s: &str) -> Result<NaiveDate, &'static str> {
+        try_date_from_str_with(s, &ParseOptions::default())
+    }
+
+    fn checked_add_offset(date: &NaiveDate, offset: i32, tag_type: &DateTag) -> Option<NaiveDate> {
+        checked_add_offset_with(date, offset, tag_type, &Calendar::default())
+    }
+
     const YEAR: i32 = 2022;
     const MONTH: u32 = 10;
     const DAY: u32 = 5;
@@ -188,6 +470,223 @@ mod tests {
         assert!(d.is_none());
     }
 
+    fn options(order: FieldOrder, short_year: bool) -> ParseOptions {
+        ParseOptions {
+            order,
+            short_year,
+            pivot: DEFAULT_CENTURY_PIVOT,
+            locale: Locale::POSIX,
+            weeks: WeekSystem::Iso,
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_short_year() {
+        let o = options(FieldOrder::Ymd, true);
+        let d = checked_date_from_str_with("240427", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let d = checked_date_from_str_with("24.04", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let d = checked_date_from_str_with("99", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(1999, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_short_year_week() {
+        let o = ParseOptions {
+            weekly: true,
+            ..options(FieldOrder::Ymd, true)
+        };
+        let formatter = DateFormatter {
+            locale: Locale::POSIX,
+            calendar: Calendar::default(),
+        };
+        let format = DateTag::W.get_format(&StylePreset::Compact.into());
+
+        for (y, m, d) in [(2024, 4, 27), (2024, 12, 30), (2021, 1, 3), (2026, 3, 2)] {
+            let date = NaiveDate::from_ymd_opt(y, m, d).unwrap();
+            let tag = formatter.format(&date, &format);
+            let parsed = checked_date_from_str_with(&tag, &o).unwrap();
+            assert_eq!(parsed.iso_week(), date.iso_week(), "{}", tag);
+        }
+        // months are still read as YYMM if dates are not weeks
+        let d = checked_date_from_str_with("2404", &options(FieldOrder::Ymd, true)).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_pivot() {
+        let mut o = options(FieldOrder::Ymd, true);
+        let d = checked_date_from_str_with("681231", &o).unwrap();
+        assert_eq!(d.year(), 2068);
+        let d = checked_date_from_str_with("690101", &o).unwrap();
+        assert_eq!(d.year(), 1969);
+        o.pivot = 50;
+        let d = checked_date_from_str_with("681231", &o).unwrap();
+        assert_eq!(d.year(), 1968);
+        o.pivot = 100;
+        let d = checked_date_from_str_with("990101", &o).unwrap();
+        assert_eq!(d.year(), 2099);
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_orders() {
+        let o = options(FieldOrder::Dmy, false);
+        let d = checked_date_from_str_with("27.04.2024", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let d = checked_date_from_str_with("04.2024", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let o = options(FieldOrder::Mdy, true);
+        let d = checked_date_from_str_with("04/27/24", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_fallback() {
+        // default format is used when the options do not match
+        let o = options(FieldOrder::Dmy, false);
+        let d = checked_date_from_str_with("2024-04-27", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let o = options(FieldOrder::Ymd, true);
+        let d = checked_date_from_str_with("20240427", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        assert!(checked_date_from_str_with("2404271", &o).is_none());
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_month_name() {
+        let mut o = options(FieldOrder::Dmy, false);
+        let d = checked_date_from_str_with("Apr2024", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let d = checked_date_from_str_with("27-April-2024", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let d = checked_date_from_str_with("2024 Apr 27", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let d = checked_date_from_str_with("27-Apr-24", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        o.locale = Locale::fr_FR;
+        let d = checked_date_from_str_with("Avril-2024", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let d = checked_date_from_str_with("27 févr. 2024", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 2, 27).unwrap());
+        assert!(checked_date_from_str_with("Avril", &o).is_none());
+        assert!(checked_date_from_str_with("31-Avril-2024", &o).is_none());
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_partitions() {
+        let o = ParseOptions::default();
+        let d = checked_date_from_str_with("year=2024/month=04/day=27", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let d = checked_date_from_str_with("yr=2024/mo=04", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let d = checked_date_from_str_with("2024Q2", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let d = checked_date_from_str_with("2024-q4", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 10, 1).unwrap());
+        assert!(checked_date_from_str_with("2024Q5", &o).is_none());
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_iso_week() {
+        let o = ParseOptions::default();
+        let d = checked_date_from_str_with("2024-W17-6", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let d = checked_date_from_str_with("2024W176", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let d = checked_date_from_str_with("2024-W17", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 22).unwrap());
+        // ISO week 2025-W01 starts in 2024
+        let d = checked_date_from_str_with("2025w01", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 12, 30).unwrap());
+        assert!(checked_date_from_str_with("2024-W54", &o).is_none());
+        assert!(checked_date_from_str_with("2024-W17-8", &o).is_none());
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_encoded() {
+        let d = checked_date_from_str_with("@19840", &ParseOptions::default()).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let o = ParseOptions {
+            encoding: Encoding::Base36,
+            epoch: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            ..ParseOptions::default()
+        };
+        let d = checked_date_from_str_with(" @39 ", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        assert!(checked_date_from_str_with("@3!", &o).is_none());
+        // other dates are still read as usual
+        let d = checked_date_from_str_with("20240427", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_us_week() {
+        let o = ParseOptions {
+            weeks: WeekSystem::Us,
+            ..ParseOptions::default()
+        };
+        let d = checked_date_from_str_with("2024-W53", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 12, 29).unwrap());
+        let d = checked_date_from_str_with("2025-W01", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+        let d = checked_date_from_str_with("2024-W17-7", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        assert!(checked_date_from_str_with("2024-W53-4", &o).is_none());
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_ordinal() {
+        let o = ParseOptions::default();
+        let d = checked_date_from_str_with("2024-118", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let d = checked_date_from_str_with("2024366", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+        assert!(checked_date_from_str_with("2023-366", &o).is_none());
+        let o = options(FieldOrder::Ymd, true);
+        let d = checked_date_from_str_with("2024-118", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+        let o = options(FieldOrder::Dmy, false);
+        let d = checked_date_from_str_with("118.2024", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 27).unwrap());
+    }
+
+    #[test]
+    fn test_checked_date_from_str_with_month_week() {
+        let o = ParseOptions::default();
+        let d = checked_date_from_str_with("2024-04-W4", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 4, 22).unwrap());
+        let d = checked_date_from_str_with("202402W5", &o).unwrap();
+        assert_eq!(d, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+        assert!(checked_date_from_str_with("2023-02-W5", &o).is_none());
+        assert!(checked_date_from_str_with("2024-04-W6", &o).is_none());
+    }
+
+    #[test]
+    fn test_checked_add_offset_week_of_month() {
+        let date = checked_add_offset(&ref_date(), 5, &DateTag::WeekOfMonth).unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(YEAR, 11, 1).unwrap());
+        let date = checked_add_offset(&ref_date(), -1, &DateTag::Mw).unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(YEAR, 9, 29).unwrap());
+    }
+
+    #[test]
+    fn test_checked_add_offset_quarter() {
+        let date = checked_add_offset(&ref_date(), 1, &DateTag::Quarterly).unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(YEAR + 1, 1, DAY).unwrap());
+        let date = checked_add_offset(&ref_date(), -2, &DateTag::Q).unwrap();
+        assert_eq!(date, NaiveDate::from_ymd_opt(YEAR, 4, DAY).unwrap());
+    }
+
+    #[test]
+    fn test_checked_add_offset_overflow() {
+        for tag_type in [DateTag::Yearly, DateTag::Quarterly, DateTag::Monthly] {
+            assert!(checked_add_offset(&ref_date(), 1_000_000_000, &tag_type).is_none());
+            assert!(checked_add_offset(&ref_date(), i32::MIN, &tag_type).is_none());
+        }
+    }
+
     #[test]
     fn test_checked_add_offset_positive_year() {
         let date = checked_add_offset(&ref_date(), 1, &DateTag::Yearly).unwrap();
@@ -0,0 +1,411 @@
+use chrono::{Datelike, NaiveDate, TimeDelta, Weekday};
+use clap::ValueEnum;
+
+/// week numbering systems
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WeekSystem {
+    /// ISO 8601 weeks, starting on Monday, week 01 contains January 4th
+    Iso,
+    /// US weeks, starting on Sunday, week 01 contains January 1st
+    Us,
+    /// weeks starting on Sunday, days before the first Sunday belong to week 00
+    Sunday,
+    /// broadcast calendar weeks, starting on Monday, week 01 contains January 1st
+    Broadcast,
+}
+
+impl WeekSystem {
+    /// first day of the week
+    pub fn first_day(&self) -> Weekday {
+        match self {
+            WeekSystem::Iso | WeekSystem::Broadcast => Weekday::Mon,
+            WeekSystem::Us | WeekSystem::Sunday => Weekday::Sun,
+        }
+    }
+
+    /// weeks are split at year boundaries
+    fn is_split(&self) -> bool {
+        matches!(self, WeekSystem::Us | WeekSystem::Sunday)
+    }
+
+    /// week-based year and week number of a date
+    pub fn week(&self, date: &NaiveDate) -> (i32, u32) {
+        match self {
+            WeekSystem::Iso => (date.iso_week().year(), date.iso_week().week()),
+            WeekSystem::Us => {
+                let first = date.with_ordinal(1).unwrap();
+                let offset = first.weekday().num_days_from_sunday();
+                (date.year(), (date.ordinal0() + offset) / 7 + 1)
+            }
+            WeekSystem::Sunday => {
+                let day = date.weekday().num_days_from_sunday();
+                (date.year(), (date.ordinal0() + 7 - day) / 7)
+            }
+            WeekSystem::Broadcast => {
+                // the week belongs to the year of its last day
+                let week = date.week(Weekday::Mon);
+                let year = week.last_day().year();
+                let start = anchor(*self, year);
+                (year, ((week.first_day() - start).num_days() / 7 + 1) as u32)
+            }
+        }
+    }
+
+    /// number of a day within its week, starting from 1
+    pub fn weekday(&self, date: &NaiveDate) -> u32 {
+        match self.first_day() {
+            Weekday::Sun => date.weekday().number_from_sunday(),
+            _ => date.weekday().number_from_monday(),
+        }
+    }
+
+    /// date of the given week and day (starting from 1), the first day of
+    /// the week is used if no day is given
+    pub fn date_of_week(&self, year: i32, week: u32, day: Option<u32>) -> Option<NaiveDate> {
+        let start = anchor(*self, year).checked_add_signed(TimeDelta::weeks(week as i64 - 1))?;
+
+        // split weeks may start in the previous year
+        let days = match day {
+            Some(d) => d..=d,
+            None => 1..=7,
+        };
+
+        days.filter_map(|d| start.checked_add_signed(TimeDelta::days(d as i64 - 1)))
+            .find(|date| self.week(date) == (year, week))
+            .filter(|date| day.is_none_or(|d| self.weekday(date) == d))
+    }
+
+    /// first and last day of the week containing the given date, if
+    /// within the supported range of dates
+    pub fn period(&self, date: &NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        let days = date.weekday().days_since(self.first_day()) as i64;
+        let mut first = date.checked_sub_signed(TimeDelta::days(days))?;
+        let mut last = first.checked_add_signed(TimeDelta::days(6))?;
+
+        if self.is_split() {
+            first = first.max(date.with_ordinal(1).unwrap());
+            last = last.min(NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap());
+        }
+
+        Some((first, last))
+    }
+
+    /// move a date by the given number of weeks
+    ///
+    /// Split weeks are counted once, moving to the first day of the
+    /// target week.
+    pub fn checked_add_weeks(&self, date: &NaiveDate, weeks: i32) -> Option<NaiveDate> {
+        if !self.is_split() {
+            return date.checked_add_signed(TimeDelta::weeks(weeks as i64));
+        }
+        if weeks == 0 {
+            return Some(*date);
+        }
+
+        // skip whole 400-year cycles, whose weekdays repeat, then years
+        let mut year = date.year();
+        let mut index = self.split_index(date) + weeks as i64;
+        let cycle = (year..year + 400)
+            .map(|y| self.split_weeks(y))
+            .sum::<Option<i64>>()?;
+        let cycles = index.div_euclid(cycle);
+        year = year.checked_add(i32::try_from(cycles * 400).ok()?)?;
+        index -= cycles * cycle;
+        while index >= self.split_weeks(year)? {
+            index -= self.split_weeks(year)?;
+            year += 1;
+        }
+
+        // weeks after the first one start on the first day of the week
+        let jan1 = NaiveDate::from_yo_opt(year, 1)?;
+        match index {
+            0 => Some(jan1),
+            _ => jan1.checked_add_signed(TimeDelta::days(index * 7 - self.jan1_offset(&jan1))),
+        }
+    }
+
+    /// days from the first day of the week to January 1st
+    fn jan1_offset(&self, jan1: &NaiveDate) -> i64 {
+        jan1.weekday().days_since(self.first_day()) as i64
+    }
+
+    /// index of the split week of a date within its year, starting from 0
+    fn split_index(&self, date: &NaiveDate) -> i64 {
+        let jan1 = date.with_ordinal(1).unwrap();
+        (self.jan1_offset(&jan1) + date.ordinal0() as i64) / 7
+    }
+
+    /// number of split weeks of a year
+    fn split_weeks(&self, year: i32) -> Option<i64> {
+        Some(self.split_index(&NaiveDate::from_ymd_opt(year, 12, 31)?) + 1)
+    }
+
+    /// value of a week-based specifier ('%G', '%g', '%V' and '%u'), given
+    /// its padding modifier, if it differs from the ISO 8601 one
+    pub fn specifier(&self, date: &NaiveDate, pad: Option<char>, spec: char) -> Option<String> {
+        if *self == WeekSystem::Iso {
+            return None;
+        }
+
+        let (year, week) = self.week(date);
+        match (pad, spec) {
+            (None, 'G') => Some(format!("{:04}", year)),
+            (None, 'g') => Some(format!("{:02}", year.rem_euclid(100))),
+            (None, 'V') => Some(format!("{:02}", week)),
+            (Some('-'), 'V') => Some(week.to_string()),
+            (Some('_'), 'V') => Some(format!("{:2}", week)),
+            (None, 'u') => Some(self.weekday(date).to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// week of the month, days 1-7 being week 1
+pub fn week_of_month(date: &NaiveDate) -> u32 {
+    date.day0() / 7 + 1
+}
+
+/// first and last day of the week of the month containing the given date
+pub fn month_week_period(date: &NaiveDate) -> (NaiveDate, NaiveDate) {
+    let first = date.with_day(date.day0() / 7 * 7 + 1).unwrap();
+    let last = (1..7)
+        .filter_map(|d| first.checked_add_signed(TimeDelta::days(d)))
+        .take_while(|d| d.month() == first.month())
+        .last()
+        .unwrap_or(first);
+
+    (first, last)
+}
+
+/// move a date by the given number of weeks of the month, moving to the
+/// first day of the target week
+pub fn checked_add_month_weeks(date: &NaiveDate, weeks: i32) -> Option<NaiveDate> {
+    let mut date = *date;
+    for _ in 0..weeks.unsigned_abs() {
+        date = if weeks > 0 {
+            month_week_period(&date).1.succ_opt()?
+        } else {
+            month_week_period(&month_week_period(&date).0.pred_opt()?).0
+        };
+    }
+
+    Some(date)
+}
+
+/// first day of week 01 of the given year
+fn anchor(system: WeekSystem, year: i32) -> NaiveDate {
+    let jan1 = NaiveDate::from_yo_opt(year, 1).unwrap();
+
+    match system {
+        WeekSystem::Iso => {
+            let jan4 = NaiveDate::from_yo_opt(year, 4).unwrap();
+            jan4.week(Weekday::Mon).first_day()
+        }
+        WeekSystem::Us | WeekSystem::Broadcast => jan1.week(system.first_day()).first_day(),
+        WeekSystem::Sunday => {
+            let days = (7 - jan1.weekday().num_days_from_sunday()) % 7;
+            jan1 + TimeDelta::days(days as i64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_week_iso_year_boundary() {
+        assert_eq!(WeekSystem::Iso.week(&ymd(2024, 12, 30)), (2025, 1));
+        assert_eq!(WeekSystem::Iso.week(&ymd(2021, 1, 3)), (2020, 53));
+    }
+
+    #[test]
+    fn test_week_us_year_boundary() {
+        // 2025-01-01 is a Wednesday, the week is split between years
+        assert_eq!(WeekSystem::Us.week(&ymd(2024, 12, 31)), (2024, 53));
+        assert_eq!(WeekSystem::Us.week(&ymd(2025, 1, 1)), (2025, 1));
+        assert_eq!(WeekSystem::Us.week(&ymd(2025, 1, 5)), (2025, 2));
+        // 2023-01-01 is a Sunday
+        assert_eq!(WeekSystem::Us.week(&ymd(2023, 1, 1)), (2023, 1));
+        assert_eq!(WeekSystem::Us.week(&ymd(2023, 1, 7)), (2023, 1));
+    }
+
+    #[test]
+    fn test_week_sunday_year_boundary() {
+        // days before the first Sunday belong to week 00
+        assert_eq!(WeekSystem::Sunday.week(&ymd(2025, 1, 1)), (2025, 0));
+        assert_eq!(WeekSystem::Sunday.week(&ymd(2025, 1, 5)), (2025, 1));
+        assert_eq!(WeekSystem::Sunday.week(&ymd(2024, 12, 31)), (2024, 52));
+        assert_eq!(WeekSystem::Sunday.week(&ymd(2023, 1, 1)), (2023, 1));
+    }
+
+    #[test]
+    fn test_week_broadcast_year_boundary() {
+        // the broadcast year 2025 starts on Monday 2024-12-30
+        assert_eq!(WeekSystem::Broadcast.week(&ymd(2024, 12, 29)), (2024, 52));
+        assert_eq!(WeekSystem::Broadcast.week(&ymd(2024, 12, 30)), (2025, 1));
+        // the broadcast year 2024 starts on Monday 2024-01-01
+        assert_eq!(WeekSystem::Broadcast.week(&ymd(2024, 1, 1)), (2024, 1));
+        // the broadcast year 2022 starts on Monday 2021-12-27
+        assert_eq!(WeekSystem::Broadcast.week(&ymd(2021, 12, 27)), (2022, 1));
+        assert_eq!(WeekSystem::Broadcast.week(&ymd(2021, 12, 26)), (2021, 52));
+    }
+
+    #[test]
+    fn test_from_week() {
+        let w = WeekSystem::Iso;
+        assert_eq!(w.date_of_week(2025, 1, None), Some(ymd(2024, 12, 30)));
+        assert_eq!(w.date_of_week(2024, 17, Some(6)), Some(ymd(2024, 4, 27)));
+        let w = WeekSystem::Us;
+        assert_eq!(w.date_of_week(2025, 1, None), Some(ymd(2025, 1, 1)));
+        assert_eq!(w.date_of_week(2024, 53, None), Some(ymd(2024, 12, 29)));
+        assert_eq!(w.date_of_week(2024, 53, Some(4)), None);
+        assert_eq!(w.date_of_week(2024, 17, Some(7)), Some(ymd(2024, 4, 27)));
+        let w = WeekSystem::Sunday;
+        assert_eq!(w.date_of_week(2025, 0, None), Some(ymd(2025, 1, 1)));
+        assert_eq!(w.date_of_week(2025, 1, None), Some(ymd(2025, 1, 5)));
+        let w = WeekSystem::Broadcast;
+        assert_eq!(w.date_of_week(2025, 1, None), Some(ymd(2024, 12, 30)));
+        assert_eq!(w.date_of_week(2024, 54, None), None);
+    }
+
+    #[test]
+    fn test_period_split_weeks() {
+        let w = WeekSystem::Us;
+        assert_eq!(
+            w.period(&ymd(2024, 12, 31)),
+            Some((ymd(2024, 12, 29), ymd(2024, 12, 31)))
+        );
+        assert_eq!(
+            w.period(&ymd(2025, 1, 2)),
+            Some((ymd(2025, 1, 1), ymd(2025, 1, 4)))
+        );
+        let w = WeekSystem::Broadcast;
+        assert_eq!(
+            w.period(&ymd(2025, 1, 2)),
+            Some((ymd(2024, 12, 30), ymd(2025, 1, 5)))
+        );
+        assert_eq!(w.period(&NaiveDate::MAX), None);
+    }
+
+    #[test]
+    fn test_checked_add_weeks() {
+        let w = WeekSystem::Us;
+        assert_eq!(
+            w.checked_add_weeks(&ymd(2024, 12, 22), 1),
+            Some(ymd(2024, 12, 29))
+        );
+        assert_eq!(
+            w.checked_add_weeks(&ymd(2024, 12, 22), 2),
+            Some(ymd(2025, 1, 1))
+        );
+        assert_eq!(
+            w.checked_add_weeks(&ymd(2025, 1, 3), -1),
+            Some(ymd(2024, 12, 29))
+        );
+        assert_eq!(
+            w.checked_add_weeks(&ymd(2025, 1, 3), 0),
+            Some(ymd(2025, 1, 3))
+        );
+        let w = WeekSystem::Iso;
+        assert_eq!(
+            w.checked_add_weeks(&ymd(2024, 12, 22), 2),
+            Some(ymd(2025, 1, 5))
+        );
+    }
+
+    #[test]
+    fn test_checked_add_weeks_split_far() {
+        // move one week at a time, as a reference
+        let step = |w: WeekSystem, date: NaiveDate, forward: bool| {
+            if forward {
+                w.period(&date).unwrap().1.succ_opt().unwrap()
+            } else {
+                let previous = w.period(&date).unwrap().0.pred_opt().unwrap();
+                w.period(&previous).unwrap().0
+            }
+        };
+
+        for w in [WeekSystem::Us, WeekSystem::Sunday] {
+            for start in [ymd(2024, 4, 27), ymd(2023, 1, 1), ymd(2021, 12, 31)] {
+                let (mut forward, mut backward) = (start, start);
+                for weeks in 1..=1200 {
+                    forward = step(w, forward, true);
+                    backward = step(w, backward, false);
+                    assert_eq!(w.checked_add_weeks(&start, weeks), Some(forward));
+                    assert_eq!(w.checked_add_weeks(&start, -weeks), Some(backward));
+                }
+            }
+        }
+
+        let w = WeekSystem::Us;
+        assert_eq!(w.checked_add_weeks(&ymd(2024, 4, 27), 2_000_000_000), None);
+        assert_eq!(w.checked_add_weeks(&ymd(2024, 4, 27), i32::MIN), None);
+        let date = w.checked_add_weeks(&ymd(2024, 4, 27), 1_000_000).unwrap();
+        assert_eq!(
+            w.checked_add_weeks(&date, -1_000_000),
+            Some(ymd(2024, 4, 21))
+        );
+    }
+
+    #[test]
+    fn test_specifier() {
+        let date = ymd(2024, 12, 31);
+        assert_eq!(WeekSystem::Iso.specifier(&date, None, 'V'), None);
+        assert_eq!(WeekSystem::Us.specifier(&date, None, 'V').unwrap(), "53");
+        assert_eq!(WeekSystem::Us.specifier(&date, None, 'u').unwrap(), "3");
+        assert_eq!(
+            WeekSystem::Sunday.specifier(&date, None, 'g').unwrap(),
+            "24"
+        );
+        assert_eq!(
+            WeekSystem::Broadcast.specifier(&date, None, 'G').unwrap(),
+            "2025"
+        );
+        let date = ymd(2025, 1, 1);
+        assert_eq!(
+            WeekSystem::Us.specifier(&date, Some('-'), 'V').unwrap(),
+            "1"
+        );
+        assert_eq!(WeekSystem::Us.specifier(&date, None, 'Y'), None);
+    }
+
+    #[test]
+    fn test_week_of_month() {
+        assert_eq!(week_of_month(&ymd(2024, 4, 27)), 4);
+        assert_eq!(week_of_month(&ymd(2024, 4, 7)), 1);
+        assert_eq!(week_of_month(&ymd(2024, 4, 29)), 5);
+        assert_eq!(
+            month_week_period(&ymd(2024, 4, 30)),
+            (ymd(2024, 4, 29), ymd(2024, 4, 30))
+        );
+        assert_eq!(
+            month_week_period(&ymd(2024, 2, 10)),
+            (ymd(2024, 2, 8), ymd(2024, 2, 14))
+        );
+    }
+
+    #[test]
+    fn test_checked_add_month_weeks() {
+        assert_eq!(
+            checked_add_month_weeks(&ymd(2024, 4, 27), 1),
+            Some(ymd(2024, 4, 29))
+        );
+        assert_eq!(
+            checked_add_month_weeks(&ymd(2024, 4, 27), 2),
+            Some(ymd(2024, 5, 1))
+        );
+        assert_eq!(
+            checked_add_month_weeks(&ymd(2024, 5, 3), -1),
+            Some(ymd(2024, 4, 29))
+        );
+        assert_eq!(
+            checked_add_month_weeks(&ymd(2024, 5, 3), -2),
+            Some(ymd(2024, 4, 22))
+        );
+    }
+}
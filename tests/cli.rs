@@ -27,6 +27,18 @@ fn test_on_stderr(args: &str, result: &str) -> Result<(), Box<dyn std::error::Er
     Ok(())
 }
 
+fn test_on_stdin(args: &str, input: &str, result: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::cargo_bin("datetag")?;
+
+    cmd.args(args.split(' '))
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(result.to_string()));
+
+    Ok(())
+}
+
 #[test]
 fn test_default_args() -> Result<(), Box<dyn std::error::Error>> {
     let d = format!("{}", Local::now().naive_local().date().format("%Y%m"));
@@ -168,3 +180,1354 @@ fn test_date_valid_day_offset() -> Result<(), Box<dyn std::error::Error>> {
 fn test_date_valid_day_offset_negative() -> Result<(), Box<dyn std::error::Error>> {
     test_on_stdout("20240427 -td -o -1", "20240426")
 }
+
+#[test]
+fn test_stdin_lines() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "--stdin -td -s dash",
+        "20240427\n2024.05\n",
+        "2024-04-27\n2024-05-01\n",
+    )
+}
+
+#[test]
+fn test_stdin_lines_offset_and_prefix() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "--stdin -tm -o1 -p REL_",
+        "20240427\n20241231\n",
+        "REL_202405\nREL_202501\n",
+    )
+}
+
+#[test]
+fn test_stdin_csv_column() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "--stdin --column 2 --delimiter ; -td --on-error skip",
+        "id;date\n1;2024-04-27\n2;2024-04-28\n",
+        "20240427\n20240428\n",
+    )
+}
+
+#[test]
+fn test_stdin_passthrough() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "--stdin -td --on-error passthrough",
+        "20240427\nn/a\n",
+        "20240427\nn/a\n",
+    )
+}
+
+#[test]
+fn test_stdin_offset_skip() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin("--stdin -ty -o 100000000 --on-error skip", "2024\n", "")
+}
+
+#[test]
+fn test_stdin_fail() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::cargo_bin("datetag")?;
+
+    cmd.args(["--stdin", "-td"])
+        .write_stdin("20240427\nn/a\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("line 2"));
+
+    Ok(())
+}
+
+#[test]
+fn test_on_error_requires_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("-td --on-error skip", "--stdin")
+}
+
+#[test]
+fn test_output_json() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 -tw -p W --output json",
+        r#"{"tag":"W202417","prefix":"W","date":"202417","suffix":"","tag_type":"weekly","style":"plain","format":"%G%V","period_start":"2024-04-22","period_end":"2024-04-28","index":0}"#,
+    )
+}
+
+#[test]
+fn test_output_jsonl_repeat() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 -tm -r2 -o1 --output jsonl",
+        r#""period_start":"2024-05-01","period_end":"2024-05-31","index":1}"#,
+    )
+}
+
+#[test]
+fn test_output_csv_custom_format() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 -td --format %d/%m/%Y --output csv",
+        "\n27/04/2024,,27/04/2024,,daily,,%d/%m/%Y,2024-04-27,2024-04-27,0\n",
+    )
+}
+
+#[test]
+fn test_output_csv_empty_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "--stdin -td --output csv",
+        "",
+        "tag,prefix,date,suffix,tag_type,style,format,period_start,period_end,index\n",
+    )
+}
+
+#[test]
+fn test_output_tsv_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "--stdin -ty --output tsv",
+        "20240427\n",
+        "tag\tprefix\tdate\tsuffix\ttag_type\tstyle\tformat\tperiod_start\tperiod_end\tindex\n\
+         2024\t\t2024\t\tyearly\tplain\t%Y\t2024-01-01\t2024-12-31\t0\n",
+    )
+}
+
+#[test]
+fn test_output_passthrough_structured() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr(
+        "--stdin --on-error passthrough --output json",
+        "requires '--output text'",
+    )
+}
+
+#[test]
+fn test_template() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("datetag")?;
+
+    cmd.args(["20240427", "-tm", "-r2", "-o1"])
+        .args([
+            "--template",
+            "{env:PROJECT}-{date:%Y%m}-{seq:03}-{{{end:%d}}}",
+        ])
+        .env("PROJECT", "ACME")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "ACME-202404-000-{30}\nACME-202405-001-{31}\n",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_template_invalid() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("20240427 --template {date}-{unknown}", "position 8")
+}
+
+#[test]
+fn test_template_missing_env() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr(
+        "20240427 --template {env:DATETAG_UNSET_VARIABLE}",
+        "DATETAG_UNSET_VARIABLE",
+    )
+}
+
+#[test]
+fn test_format_custom() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 --format %d.%m.%Y", "27.04.2024")
+}
+
+#[test]
+fn test_format_unsupported_specifier() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr(
+        "20240427 --format %Y%Q",
+        "unsupported specifier '%Q' at position 3",
+    )
+}
+
+#[test]
+fn test_format_time_specifier() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("20240427 --format %Y%H", "requires time or time zone data")
+}
+
+#[test]
+fn test_list_formats() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 --list-formats", "%j   118")
+}
+
+#[test]
+fn test_date_valid_day_underscore() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s underscore", "2024_04_27")
+}
+
+#[test]
+fn test_date_valid_day_european() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s european", "27.04.2024")
+}
+
+#[test]
+fn test_date_valid_day_us() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s us", "04/27/2024")
+}
+
+#[test]
+fn test_date_valid_month_us() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -tm -s us", "04/2024")
+}
+
+#[test]
+fn test_date_valid_day_custom_style() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td --separator _ --order dmy", "27_04_2024")
+}
+
+#[test]
+fn test_date_valid_day_style_override() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s european --separator -", "27-04-2024")
+}
+
+#[test]
+fn test_date_valid_day_compact() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s compact", "240427")
+}
+
+#[test]
+fn test_date_valid_week_compact() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -tw -s compact", "2417")
+}
+
+#[test]
+fn test_date_valid_month_short_year() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -tm -s dot --short-year", "24.04")
+}
+
+#[test]
+fn test_date_compact_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("240427 -td -s compact -o1", "240428")
+}
+
+#[test]
+fn test_date_compact_century_pivot() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "680101 -ty -s compact --format %Y --century-pivot 50",
+        "1968",
+    )
+}
+
+#[test]
+fn test_date_european_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("27.04.2024 -td -s european", "27.04.2024")
+}
+
+#[test]
+fn test_date_invalid() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("2024-13-01 -td", "wrong reference date '2024-13-01'")
+}
+
+#[test]
+fn test_date_valid_month_name_locale() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -tm -s month-name --locale fr_FR", "avril-2024")
+}
+
+#[test]
+fn test_date_valid_month_abbr() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -tm -s month-abbr --separator=", "Apr2024")
+}
+
+#[test]
+fn test_date_valid_day_month_abbr() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s month-abbr --locale de_DE", "27-Apr-2024")
+}
+
+#[test]
+fn test_format_localized() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 --format %A-%B --locale it_IT", "sabato-aprile")
+}
+
+#[test]
+fn test_date_localized_month_name_input() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("Avril-2024 -tm --locale fr_FR -o1", "202405")
+}
+
+#[test]
+fn test_locale_unknown() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("20240427 --locale xx_YY", "unknown locale 'xx_YY'")
+}
+
+#[test]
+fn test_date_valid_quarter() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -tq -o1", "2024Q3")
+}
+
+#[test]
+fn test_date_quarter_offset_overflow() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("20240427 -tq -o 1000000000", "wrong date offset")
+}
+
+#[test]
+fn test_date_period_overflow() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("20241201 -tm -o 3121416", "wrong date offset")?;
+    test_on_stderr("20241201 -tq -o 1040472", "wrong date offset")
+}
+
+#[test]
+fn test_date_quarter_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("2024-Q2 -td -s dash", "2024-04-01")
+}
+
+#[test]
+fn test_date_valid_day_hive() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s hive", "year=2024/month=04/day=27")
+}
+
+#[test]
+fn test_date_valid_week_hive() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -tw -s hive", "year=2024/week=17")
+}
+
+#[test]
+fn test_date_valid_quarter_hive_keys() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 -tq -s hive --hive-key year=yr --hive-key quarter=qtr",
+        "yr=2024/qtr=2",
+    )
+}
+
+#[test]
+fn test_date_hive_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("year=2024/month=04/day=27 -td -s slash", "2024/04/27")
+}
+
+#[test]
+fn test_hive_key_without_hive_style() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("20240427 --hive-key month=mo", "requires '--style hive'")
+}
+
+#[test]
+fn test_hive_key_invalid() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("20240427 -s hive --hive-key month", "expected 'FIELD=KEY'")
+}
+
+#[test]
+fn test_until_range() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "20240427 -tm -s dash -u 20240701",
+        "",
+        "2024-04\n2024-05\n2024-06\n2024-07\n",
+    )
+}
+
+#[test]
+fn test_until_range_backward() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin("20240427 -tq -u 2023-12-31", "", "2024Q2\n2024Q1\n2023Q4\n")
+}
+
+#[test]
+fn test_until_range_offset() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "20240427 -td -o2 -u 20240502",
+        "",
+        "20240427\n20240429\n20240501\n",
+    )
+}
+
+#[test]
+fn test_until_conflicts_with_repeat() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("20240427 -u 20240701 -r3", "cannot be used with")
+}
+
+#[test]
+fn test_mkdir_hive_range() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    let args = format!(
+        "20240430 -td -s hive -u 20240501 --mkdir {}",
+        root.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "year=2024/month=05/day=01")?;
+
+    root.child("year=2024/month=04/day=30")
+        .assert(predicate::path::is_dir());
+    root.child("year=2024/month=05/day=01")
+        .assert(predicate::path::is_dir());
+
+    Ok(())
+}
+
+#[test]
+fn test_mkdir_nested() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    let args = format!(
+        "20240427 -tm -s slash --mkdir {}",
+        root.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "2024/04")?;
+
+    root.child("2024/04").assert(predicate::path::is_dir());
+
+    Ok(())
+}
+
+#[test]
+fn test_date_valid_week_iso() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -tw -s iso-week", "2024-W17")
+}
+
+#[test]
+fn test_date_valid_week_iso_compact() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -tw -s iso-week-compact", "2024W17")
+}
+
+#[test]
+fn test_date_valid_week_day_iso() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -t week-day -s iso-week", "2024-W17-6")
+}
+
+#[test]
+fn test_date_valid_week_day_year_boundary() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20241231 -t wd -s iso-week", "2025-W01-2")
+}
+
+#[test]
+fn test_date_week_day_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("2024-W17-6 -td -s dash", "2024-04-27")
+}
+
+#[test]
+fn test_date_week_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("2024W17 -tw -s iso-week -o1", "2024-W18")
+}
+
+#[test]
+fn test_week_system_us_year_boundary() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "20241222 -tw -s iso-week --week-system us -r4 -o1",
+        "",
+        "2024-W52\n2024-W53\n2025-W01\n2025-W02\n",
+    )
+}
+
+#[test]
+fn test_week_system_us_backward() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20250103 -tw --week-system us -o-1", "202453")
+}
+
+#[test]
+fn test_week_system_sunday_week_zero() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20250101 -tw --week-system sunday", "202500")
+}
+
+#[test]
+fn test_week_system_broadcast_year_boundary() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20241230 -tw -s iso-week --week-system broadcast",
+        "2025-W01",
+    )
+}
+
+#[test]
+fn test_week_system_parse() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("2025-W01 -td --week-system sunday", "20250105")
+}
+
+#[test]
+fn test_week_system_week_day() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20241231 -t wd -s iso-week --week-system us", "2024-W53-3")
+}
+
+#[test]
+fn test_date_valid_ordinal() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -t ordinal -s dash", "2024-118")
+}
+
+#[test]
+fn test_date_ordinal_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("2024-118 -td -s dash", "2024-04-27")
+}
+
+#[test]
+fn test_date_ordinal_offset() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("2024366 -to -o1", "2025001")
+}
+
+#[test]
+fn test_date_valid_week_of_month() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -t week-of-month -s dash", "2024-04-W4")
+}
+
+#[test]
+fn test_date_week_of_month_range() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "2024-04-W4 -t mw -s dash -u 20240508",
+        "",
+        "2024-04-W4\n2024-04-W5\n2024-05-W1\n2024-05-W2\n",
+    )
+}
+
+#[test]
+fn test_format_week_of_month() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240429 --format %b-W%K", "Apr-W5")
+}
+
+#[test]
+fn test_retail_period_week() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 --calendar retail -t mw", "FY24-P03-W3")
+}
+
+#[test]
+fn test_retail_pattern_445() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 --calendar retail --retail-pattern 445 --retail-end-month 12 --retail-year-end last --retail-year-name end -t mw",
+        "FY24-P04-W4",
+    )
+}
+
+#[test]
+fn test_retail_53_week_year() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "20240203 --calendar retail -tw -r3 -o1",
+        "",
+        "FY23-W53\nFY24-W01\nFY24-W02\n",
+    )
+}
+
+#[test]
+fn test_retail_period_range() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "20240101 --calendar retail -tm -u 20240301",
+        "",
+        "FY23-P12\nFY24-P01\n",
+    )
+}
+
+#[test]
+fn test_retail_year_offset() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240203 --calendar retail -tm -o-12", "FY22-P12")
+}
+
+#[test]
+fn test_retail_year_name_end() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 --calendar retail --retail-year-name end -tm",
+        "FY25-P03",
+    )
+}
+
+#[test]
+fn test_retail_plain_style() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 --calendar retail -tq -s dash", "2024-Q1")
+}
+
+#[test]
+fn test_julian_day() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td --calendar julian-day", "2460428")
+}
+
+#[test]
+fn test_julian_day_format() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td --format JD%J", "JD2460428")
+}
+
+#[test]
+fn test_julian_day_requires_daily() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("20240427 -tm --calendar julian-day", "requires daily tags")
+}
+
+#[test]
+fn test_japanese_era() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s dot --calendar japanese", "R06.04.27")
+}
+
+#[test]
+fn test_japanese_era_change() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20190430 -td -s dash --calendar japanese -r2 -o1 -p log_",
+        "log_H31-04-30\nlog_R01-05-01\n",
+    )
+}
+
+#[test]
+fn test_hijri() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s dash --calendar hijri", "1445-10-18")
+}
+
+#[test]
+fn test_hijri_month_range() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 -tm --calendar hijri -u 20240615",
+        "144510\n144511\n144512\n",
+    )
+}
+
+#[test]
+fn test_hebrew() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 -tm -s month-name --calendar hebrew -x _rel",
+        "Nisan-5784_rel",
+    )
+}
+
+#[test]
+fn test_hebrew_year_offset() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 --calendar hebrew -ty -o1", "5785")
+}
+
+#[test]
+fn test_epoch_day() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -t epoch-day", "19840")
+}
+
+#[test]
+fn test_epoch_seconds() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -t es -p ts_", "ts_1714176000")
+}
+
+#[test]
+fn test_base36_offset() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -t base36 -r3 -o1", "fb4\nfb5\nfb6\n")
+}
+
+#[test]
+fn test_hex_custom_epoch() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -t hex --epoch 2024-01-01", "75")
+}
+
+#[test]
+fn test_encoded_decoding() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("@fb4 -t b36 --format %F", "2024-04-27")
+}
+
+#[test]
+fn test_encoded_decoding_seconds() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("@1714200000 -t epoch-seconds -o1", "1714262400")
+}
+
+#[test]
+fn test_encoded_decoding_error() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("@fbz! -t base36", "wrong reference date '@fbz!'")
+}
+
+#[test]
+fn test_wrong_epoch() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr(
+        "20240427 -t ed --epoch 2024-13-01",
+        "wrong epoch '2024-13-01'",
+    )
+}
+
+#[test]
+fn test_calver_first_release() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin("calver YY.0M.MICRO 20240427", "", "24.04.0\n")
+}
+
+#[test]
+fn test_calver_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "calver YY.0M.MICRO 20240427 --stdin",
+        "24.03.4\n24.04.0\n24.04.1\n",
+        "24.04.2\n",
+    )
+}
+
+#[test]
+fn test_calver_reset() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "calver YYYY.0M.0D.N 20240427 --stdin --prefix v",
+        "v2024.04.26.0\nv2024.04.26.1\n2024.04.27.0\n",
+        "v2024.04.27.0\n",
+    )
+}
+
+#[test]
+fn test_calver_file() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("versions.txt")?;
+    file.write_str("2024.17.0\n2024.17.1\n")?;
+    let args = format!(
+        "calver YYYY.WW.MICRO 2024-04-27 --from {}",
+        file.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "2024.17.2")
+}
+
+#[test]
+fn test_calver_existing_version() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("versions.txt")?;
+    file.write_str("2024.04\n")?;
+    let args = format!(
+        "calver YYYY.0M 20240427 --from {}",
+        file.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stderr(&args, "version '2024.04' already exists")
+}
+
+#[test]
+fn test_calver_wrong_scheme() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("calver YY.0M.PATCH", "unknown CalVer token 'PATCH'")
+}
+
+#[test]
+fn test_target_semver() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s dot --target semver", "2024.4.27")
+}
+
+#[test]
+fn test_target_semver_prerelease() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 -tm -s dot -p 1.2.0-nightly. --target semver",
+        "1.2.0-nightly.2024.4",
+    )
+}
+
+#[test]
+fn test_target_semver_error() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr(
+        "20240427 -td --target semver",
+        "SemVer requires MAJOR.MINOR.PATCH",
+    )
+}
+
+#[test]
+fn test_target_pep440() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 -tm -s dot -p v --suffix=-dev --target pep440",
+        "2024.4.dev0",
+    )
+}
+
+#[test]
+fn test_target_debian() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 -td -s underscore --suffix=-1 --target debian",
+        "2024.04.27-1",
+    )
+}
+
+#[test]
+fn test_target_rpm() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("20240427 -td -s dash --target rpm", "2024.04.27")
+}
+
+#[test]
+fn test_target_oci_tag() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout(
+        "20240427 -td -s slash -p build: --target oci-tag",
+        "build_2024_04_27",
+    )
+}
+
+#[test]
+fn test_target_oci_tag_error() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr(
+        "20240427 -td -p .cache- --target oci-tag",
+        "OCI tags cannot start with '.'",
+    )
+}
+
+#[test]
+fn test_target_calver() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdout("--target semver calver YY.0M.MICRO 20240427", "24.4.0")
+}
+
+#[test]
+fn test_unique_state() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    let args = format!(
+        "20240427 -td -p LAB_ --unique --state {}",
+        root.child("state").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "LAB_20240427_01")?;
+    test_on_stdout(&args, "LAB_20240427_02")?;
+
+    root.child("state")
+        .assert(predicate::str::contains("LAB_20240427\t2"));
+
+    Ok(())
+}
+
+#[test]
+fn test_unique_repeat() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    let args = format!(
+        "20240427 -td -r2 -o0 --unique --state {}",
+        root.child("state").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "20240427_01\n20240427_02")
+}
+
+#[test]
+fn test_unique_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("LAB_20240427_03.tar.gz").touch()?;
+    let args = format!(
+        "20240427 -td -p LAB_ --unique --unique-dir {}",
+        root.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "LAB_20240427_04")
+}
+
+#[test]
+fn test_unique_requires_unique() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("20240427 --unique-git", "--unique")
+}
+
+#[test]
+fn test_scan_tree() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("backup_20240427.tar").touch()?;
+    root.child("logs/app_2024-03-03.log").touch()?;
+    root.child("notes.txt").touch()?;
+    let args = format!("scan {}", root.path().to_str().unwrap_or_default());
+
+    test_on_stdout(
+        &args,
+        "202403 (1)\n  logs/app_2024-03-03.log\n202404 (1)\n  backup_20240427.tar\n",
+    )
+}
+
+#[test]
+fn test_scan_table_filters() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("backup_20240427.tar").touch()?;
+    root.child("backup_2024-04-20.tar").touch()?;
+    root.child("db_2024.04.27.sql").touch()?;
+    let args = format!(
+        "-s dash scan {} --view table --group-by weekly --prefix backup_ --since 2024-04-22",
+        root.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(
+        &args,
+        "GROUP    TAG       TYPE   PATH\n2024-17  20240427  daily  backup_20240427.tar\n",
+    )
+}
+
+#[test]
+fn test_scan_json() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("sales-2024Q2.csv").touch()?;
+    let args = format!(
+        "scan {} --view json --group-by yearly",
+        root.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "\"tag_type\": \"quarterly\"")
+}
+
+#[test]
+fn test_prune_print() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "prune --stdin --keep-daily 1 --keep-monthly 2",
+        "backup_20240427.tar\nbackup_20240426.tar\nbackup_20240331.tar\nbackup_20240315.tar\nnotes.txt\n",
+        "backup_20240426.tar\nbackup_20240315.tar\n",
+    )
+}
+
+#[test]
+fn test_prune_dry_run() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("backup_20240427.tar").touch()?;
+    root.child("backup_20240426.tar").touch()?;
+    let args = format!(
+        "prune {} --keep-daily 1 --action delete",
+        root.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "delete  backup_20240426.tar\ndry run")?;
+
+    root.child("backup_20240426.tar")
+        .assert(predicate::path::exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_prune_move() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("backup_20240427.tar").touch()?;
+    root.child("backup_20240420.tar").touch()?;
+    root.child("backup_20240419.tar").touch()?;
+    let args = format!(
+        "prune {} --keep-weekly 2 --action move --to {} --apply",
+        root.path().to_str().unwrap_or_default(),
+        root.child("old").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "move    backup_20240419.tar")?;
+
+    root.child("backup_20240419.tar")
+        .assert(predicate::path::missing());
+    root.child("old/backup_20240419.tar")
+        .assert(predicate::path::exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_prune_directories() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("2024-04/report.pdf").touch()?;
+    root.child("backup_20240427.tar").touch()?;
+    root.child("backup_20240426.tar").touch()?;
+    let args = format!(
+        "prune {} --keep-daily 1 --action delete --apply",
+        root.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(
+        &args,
+        "keep    backup_20240427.tar (daily)\ndelete  backup_20240426.tar",
+    )?;
+    root.child("2024-04/report.pdf")
+        .assert(predicate::path::exists());
+    root.child("backup_20240426.tar")
+        .assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_prune_without_rules() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stderr("prune", "at least one '--keep-*' rule is required")
+}
+
+#[test]
+fn test_rename_dry_run() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("report.pdf").touch()?;
+    let args = format!(
+        "-td rename {} --date 20240427",
+        root.child("report.pdf").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "20240427_report.pdf\ndry run")?;
+
+    root.child("report.pdf").assert(predicate::path::exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_apply_and_undo() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("backup.tar.gz").touch()?;
+    let journal = root.child("journal");
+    let args = format!(
+        "-td -s dash rename {} --date 20240427 --position suffix --apply --journal {}",
+        root.child("backup.tar.gz")
+            .path()
+            .to_str()
+            .unwrap_or_default(),
+        journal.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "backup_2024-04-27.tar.gz")?;
+    root.child("backup_2024-04-27.tar.gz")
+        .assert(predicate::path::exists());
+
+    let args = format!(
+        "rename --undo --apply --journal {}",
+        journal.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "backup.tar.gz")?;
+    root.child("backup.tar.gz")
+        .assert(predicate::path::exists());
+    journal.assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_journal_kept_and_undo_resumed() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("a.txt").touch()?;
+    root.child("b.txt").touch()?;
+    let journal = root.child("journal");
+    let path = |name: &str| {
+        root.child(name)
+            .path()
+            .to_str()
+            .unwrap_or_default()
+            .to_string()
+    };
+    let journal_arg = format!("--apply --journal {}", path("journal"));
+
+    let args = format!(
+        "-td rename {} {} --date 20240427 {}",
+        path("a.txt"),
+        path("b.txt"),
+        journal_arg
+    );
+    test_on_stdout(&args, "20240427_a.txt")?;
+
+    // a run with nothing to rename keeps the journal of the previous one
+    let args = format!(
+        "-td rename {} {} --date 20240427 --position replace {}",
+        path("20240427_a.txt"),
+        path("20240427_b.txt"),
+        journal_arg
+    );
+    test_on_stdout(&args, "(already up to date)")?;
+    journal.assert(predicate::str::contains("20240427_b.txt"));
+
+    // files already restored are skipped when undoing
+    std::fs::rename(path("20240427_a.txt"), path("a.txt"))?;
+    let args = format!("rename --undo {}", journal_arg);
+    test_on_stdout(&args, "a.txt (already restored)")?;
+    root.child("b.txt").assert(predicate::path::exists());
+    journal.assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_replace() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("log_2024.04.03.txt").touch()?;
+    root.child("notes.txt").touch()?;
+    let args = format!(
+        "-td rename {} {} --date 20240427 --position replace",
+        root.child("log_2024.04.03.txt")
+            .path()
+            .to_str()
+            .unwrap_or_default(),
+        root.child("notes.txt").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "notes.txt (no tag to replace)")?;
+    test_on_stdout(&args, "log_20240427.txt")
+}
+
+#[test]
+fn test_rename_replace_up_to_date() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("20240427_a.txt").touch()?;
+    let args = format!(
+        "-td rename {} --date 20240427 --position replace",
+        root.child("20240427_a.txt")
+            .path()
+            .to_str()
+            .unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "20240427_a.txt (already up to date)")
+}
+
+#[test]
+fn test_rename_conflict() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("a.pdf").touch()?;
+    root.child("20240427_a.pdf").touch()?;
+    let args = format!(
+        "-td rename {} --date 20240427",
+        root.child("a.pdf").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stderr(&args, "already exists")?;
+    test_on_stdout(
+        &format!("{} --on-conflict number", args),
+        "20240427_a_2.pdf",
+    )
+}
+
+#[test]
+fn test_rename_format_time_specifier() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("a.txt").touch()?;
+    let args = format!(
+        "--format %H -td rename {} --date 20240427",
+        root.child("a.txt").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stderr(&args, "wrong date format")
+}
+
+#[test]
+fn test_normalize_preview() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("2024.04.03_x").touch()?;
+    root.child("2024-04-03_y").touch()?;
+    root.child("20240403_z").touch()?;
+    root.child("2024.04_w").touch()?;
+    let args = format!(
+        "-td -s dash normalize {}",
+        root.path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(
+        &args,
+        "-2024.04.03_x\n+2024-04-03_x\n\
+         ! 2024.04_w (cannot convert monthly tags into daily tags)\n\
+         -20240403_z\n+2024-04-03_z\n\
+         dry run: 2 entries would be renamed",
+    )?;
+
+    root.child("2024.04.03_x").assert(predicate::path::exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_normalize_apply() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("logs_2024.04/app_2024.04.03.log").touch()?;
+    let args = format!(
+        "-tm -s dash normalize {} --apply --journal {}",
+        root.path().to_str().unwrap_or_default(),
+        root.child("journal").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "+logs_2024-04")?;
+
+    root.child("logs_2024-04/app_2024-04.log")
+        .assert(predicate::path::exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_archive_dry_run() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("backup_20240427.tar").touch()?;
+    let args = format!(
+        "-tm -s slash archive {} --root {} --source name",
+        root.child("backup_20240427.tar")
+            .path()
+            .to_str()
+            .unwrap_or_default(),
+        root.child("archive").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "dry run: 1 files would be moved, 0 skipped")?;
+
+    root.child("archive").assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_archive_conflicts() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("report.pdf").touch()?;
+    root.child("archive/2024-W17/report.pdf").touch()?;
+    let args = format!(
+        "-tw -s iso-week archive {} --root {} --date 20240427 --apply",
+        root.child("report.pdf").path().to_str().unwrap_or_default(),
+        root.child("archive").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "(already archived)\n0 files moved, 1 skipped")?;
+
+    test_on_stdout(
+        &format!("{} --copy --on-conflict rename", args),
+        "1 files copied, 0 skipped",
+    )?;
+    root.child("archive/2024-W17/report_2.pdf")
+        .assert(predicate::path::exists());
+    root.child("report.pdf").assert(predicate::path::exists());
+
+    test_on_stdout(
+        &format!("{} --on-conflict overwrite", args),
+        "1 files moved, 0 skipped",
+    )?;
+    root.child("report.pdf").assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_archive_overwrite_same_run() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("a/report.pdf").write_str("a")?;
+    root.child("b/report.pdf").write_str("b")?;
+    root.child("archive/2024/04/report.pdf").write_str("old")?;
+    let args = format!(
+        "-tm -s slash archive {} {} --root {} --date 20240427 --on-conflict overwrite --apply",
+        root.child("a/report.pdf")
+            .path()
+            .to_str()
+            .unwrap_or_default(),
+        root.child("b/report.pdf")
+            .path()
+            .to_str()
+            .unwrap_or_default(),
+        root.child("archive").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "2 files moved, 0 skipped")?;
+    root.child("archive/2024/04/report.pdf").assert("a");
+    root.child("archive/2024/04/report_2.pdf").assert("b");
+
+    Ok(())
+}
+
+#[test]
+fn test_archive_in_place() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("archive/2024/04/report.pdf")
+        .write_str("content")?;
+    let args = format!(
+        "-tm -s slash archive {} --root {} --date 20240427 --on-conflict overwrite --apply",
+        root.child("archive/2024/04/report.pdf")
+            .path()
+            .to_str()
+            .unwrap_or_default(),
+        root.child("archive").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stdout(&args, "(already archived)\n0 files moved, 1 skipped")?;
+    test_on_stdout(
+        &format!("{} --copy", args),
+        "(already archived)\n0 files copied, 1 skipped",
+    )?;
+    root.child("archive/2024/04/report.pdf").assert("content");
+
+    Ok(())
+}
+
+#[test]
+fn test_archive_folder_outside_root() -> Result<(), Box<dyn std::error::Error>> {
+    let root = assert_fs::TempDir::new()?;
+    root.child("report.pdf").touch()?;
+    let args = format!(
+        "archive {} --root {} --date 20240427 --apply",
+        root.child("report.pdf").path().to_str().unwrap_or_default(),
+        root.child("archive").path().to_str().unwrap_or_default()
+    );
+
+    test_on_stderr(
+        &format!("--format /tmp/%Y {}", args),
+        "folder '/tmp/2024' is outside of the archive root",
+    )?;
+    test_on_stderr(
+        &format!("--format ../%Y {}", args),
+        "is outside of the archive root",
+    )?;
+    root.child("report.pdf").assert(predicate::path::exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_sort_mixed_styles() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "sort",
+        "app_2024-04-27.log\nREADME\nLAB_202403_rel\nv1_20240315\n",
+        "LAB_202403_rel\nv1_20240315\napp_2024-04-27.log\nREADME\n",
+    )
+}
+
+#[test]
+fn test_sort_reverse_drop() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "sort --reverse --untagged drop",
+        "2024.03.01\nREADME\n27-Apr-2024\n",
+        "27-Apr-2024\n2024.03.01\n",
+    )
+}
+
+#[test]
+fn test_filter_range() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "filter --since 20240401 --until 20240430",
+        "backup_20240331.tar\nbackup_20240401.tar\nrelease-2024Q2\nnotes.txt\n",
+        "backup_20240401.tar\nrelease-2024Q2\n",
+    )
+}
+
+#[test]
+fn test_filter_untagged_fail() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::cargo_bin("datetag")?;
+
+    cmd.args(["filter", "--untagged", "fail"])
+        .write_stdin("backup_20240401.tar\nnotes.txt\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "no date tag at line 2: 'notes.txt'",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_gaps_missing() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::cargo_bin("datetag")?;
+
+    cmd.args(["-td", "-s", "dash", "gaps", "--stdin"])
+        .write_stdin("export_20240401.csv\nexport_2024-04-03.csv\nREADME\n")
+        .assert()
+        .failure()
+        .stdout(predicate::str::diff(
+            "missing       2024-04-02\n3 expected, 1 missing, 0 duplicates, 0 unexpected, 0 out of range\n",
+        ))
+        .stderr(predicate::str::contains("1 missing periods"));
+
+    Ok(())
+}
+
+#[test]
+fn test_gaps_duplicates() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    dir.child("db_202403.sql").touch()?;
+    dir.child("db_20240415.sql").touch()?;
+    dir.child("db_20240428.sql").touch()?;
+    dir.child("logs_202402.tar").touch()?;
+    let dir = dir.path().to_str().unwrap_or_default();
+
+    test_on_stdout(
+        &format!("-tm gaps {} --prefix db_", dir),
+        "duplicate     202404: db_20240415.sql, db_20240428.sql\n\
+         2 expected, 0 missing, 1 duplicates, 0 unexpected, 0 out of range\n",
+    )?;
+    test_on_stderr(
+        &format!("-tm gaps {} --prefix db_ --strict", dir),
+        "1 irregular periods",
+    )
+}
+
+#[test]
+fn test_gaps_step_and_range() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "-tw -s iso-week gaps --stdin --step 2 --since 2024W14",
+        "2024W12\n2024W14\n2024W15\n2024W16\n",
+        "out of range  2024-W12: 2024W12\n\
+         unexpected    2024-W15: 2024W15\n\
+         2 expected, 0 missing, 0 duplicates, 1 unexpected, 1 out of range\n",
+    )
+}
+
+#[test]
+fn test_extract_file() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("CHANGELOG.md")?;
+    file.write_str("## 1.4.0 - 2024-04-27\n- call 555-2024-0427, see 2024Q2\n")?;
+    let path = file.path().to_str().unwrap_or_default();
+
+    test_on_stdout(
+        &format!("extract {}", path),
+        &format!(
+            "{0}:1:12\t2024-04-27\t2024-04-27\tdaily\t2024-04-27..2024-04-27\n\
+             {0}:2:27\t2024Q2\t2024-04-01\tquarterly\t2024-04-01..2024-06-30\n",
+            path
+        ),
+    )
+}
+
+#[test]
+fn test_extract_json() -> Result<(), Box<dyn std::error::Error>> {
+    test_on_stdin(
+        "extract --json --strictness strict",
+        "build 20240427\nreleased 27-Apr-2024\n",
+        "{\"line\":2,\"column\":10,\"tag\":\"27-Apr-2024\",\"date\":\"2024-04-27\",\
+         \"tag_type\":\"daily\",\"period_start\":\"2024-04-27\",\"period_end\":\"2024-04-27\"}\n",
+    )
+}
+
+#[test]
+fn test_extract_nothing() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::cargo_bin("datetag")?;
+
+    cmd.args(["extract", "--min-year", "2000"])
+        .write_stdin("call +1 800-2024-12 before 1999\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no date tag found"));
+
+    Ok(())
+}